@@ -1,36 +1,44 @@
 use crate::{
-    config,
-    daemon_response::DaemonResponseSender,
+    command_recording::{self, RecordedAction},
+    config::{self, scss::SplitStylesheet},
+    daemon_response::{DaemonResponse, DaemonResponseSender, ErrorKind},
     display_backend::DisplayBackend,
-    error_handling_ctx,
-    gtk::prelude::{ContainerExt, CssProviderExt, GtkWindowExt, StyleContextExt, WidgetExt},
+    error_handling_ctx::{self, ErrorHistoryEntry},
+    gtk::prelude::{Cast, ContainerExt, CssProviderExt, GtkWindowExt, StyleContextExt, WidgetExt},
+    opts::{AlreadyOpenBehavior, WindowPosition},
     paths::EwwPaths,
     script_var_handler::ScriptVarHandlerHandle,
     state::scope_graph::{ScopeGraph, ScopeIndex},
+    window_arguments::WindowArguments,
     *,
 };
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use codespan_reporting::files::Files;
-use eww_shared_util::{Span, VarName};
+use eww_shared_util::{AttrName, Span, VarName};
 use glib::ObjectExt;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
-use simplexpr::dynval::DynVal;
+use simplexpr::{dynval::DynVal, SimplExpr};
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
     rc::Rc,
+    time::Duration,
 };
 use tokio::sync::mpsc::UnboundedSender;
 use yuck::{
     config::{
+        backend_window_options::Side,
         monitor::MonitorIdentifier,
         script_var_definition::ScriptVarDefinition,
+        window_animation::SlideAnimation,
         window_definition::WindowDefinition,
         window_geometry::{AnchorPoint, WindowGeometry},
     },
     error::DiagError,
+    format_diagnostic::lalrpop_error_to_diagnostic,
     gen_diagnostic,
+    parser::from_ast::FromAst,
     value::Coords,
 };
 
@@ -40,49 +48,196 @@ use yuck::{
 #[derive(Debug)]
 pub enum DaemonCommand {
     NoOp,
+    /// Respond to the sender once the command is handled, proving that the main loop is running
+    Ping(DaemonResponseSender),
     UpdateVars(Vec<(VarName, DynVal)>),
     ReloadConfigAndCss(DaemonResponseSender),
+    ReloadCss(DaemonResponseSender),
+    ReloadConfig(DaemonResponseSender),
     OpenInspector,
     OpenMany {
         windows: Vec<String>,
         should_toggle: bool,
+        if_open: AlreadyOpenBehavior,
         sender: DaemonResponseSender,
     },
     OpenWindow {
         window_name: String,
-        pos: Option<Coords>,
+        instance_id: Option<String>,
+        pos: Option<WindowPosition>,
         size: Option<Coords>,
         anchor: Option<AnchorPoint>,
+        close_after: Option<Duration>,
+        relative_to: Option<String>,
         screen: Option<MonitorIdentifier>,
+        all_monitors: bool,
         should_toggle: bool,
+        if_open: AlreadyOpenBehavior,
+        args: Vec<(VarName, DynVal)>,
+        sender: DaemonResponseSender,
+    },
+    GetWindowGeometry {
+        window_name: String,
+        pos: Option<Coords>,
+        size: Option<Coords>,
+        anchor: Option<AnchorPoint>,
+        screen: Option<MonitorIdentifier>,
+        sender: DaemonResponseSender,
+    },
+    MoveWindow {
+        instance_id: String,
+        pos: Option<Coords>,
+        size: Option<Coords>,
+        anchor: Option<AnchorPoint>,
+        sender: DaemonResponseSender,
+    },
+    SetWindowClass {
+        instance_id: String,
+        classes: Vec<String>,
         sender: DaemonResponseSender,
     },
     CloseWindows {
         windows: Vec<String>,
         sender: DaemonResponseSender,
     },
+    SwitchConfig {
+        config_dir: std::path::PathBuf,
+        windows: Vec<String>,
+        sender: DaemonResponseSender,
+    },
+    FocusWindow {
+        previous: bool,
+        sender: DaemonResponseSender,
+    },
+    ReassertGeometry(DaemonResponseSender),
+    ForgetGeometry {
+        windows: Vec<String>,
+        sender: DaemonResponseSender,
+    },
+    /// A monitor was connected or disconnected
+    MonitorsChanged,
+    Redraw {
+        windows: Vec<String>,
+        sender: DaemonResponseSender,
+    },
     KillServer,
     CloseAll,
+    PauseScriptVars,
+    ResumeScriptVars,
+    RestartScriptVar {
+        name: VarName,
+        sender: DaemonResponseSender,
+    },
+    SetVar {
+        name: VarName,
+        value: DynVal,
+        persist: bool,
+        sender: DaemonResponseSender,
+    },
     PrintState {
         all: bool,
+        json: bool,
         sender: DaemonResponseSender,
     },
     GetVar {
         name: String,
         sender: DaemonResponseSender,
     },
+    GetWidgetAttribute {
+        window_id: String,
+        widget_id: String,
+        attribute: String,
+        sender: DaemonResponseSender,
+    },
+    InspectWindow {
+        window_id: String,
+        sender: DaemonResponseSender,
+    },
+    Screenshot {
+        window_id: String,
+        path: std::path::PathBuf,
+        sender: DaemonResponseSender,
+    },
+    GetWindowMonitor {
+        window_id: String,
+        sender: DaemonResponseSender,
+    },
+    Subscribe {
+        vars: Vec<VarName>,
+        sender: DaemonResponseSender,
+    },
+    WaitForVar {
+        name: VarName,
+        predicate: String,
+        timeout: Option<Duration>,
+        sender: DaemonResponseSender,
+    },
+    ListWindowsWithGeometry {
+        json: bool,
+        sender: DaemonResponseSender,
+    },
+    IsWindowOpen {
+        window_id: String,
+        sender: DaemonResponseSender,
+    },
+    PrintActiveVars {
+        json: bool,
+        sender: DaemonResponseSender,
+    },
     PrintDebug(DaemonResponseSender),
+    DumpConfig(DaemonResponseSender),
     PrintGraph(DaemonResponseSender),
-    PrintWindows(DaemonResponseSender),
+    PrintWindows {
+        json: bool,
+        sender: DaemonResponseSender,
+    },
+    PrintBuiltinWidgets(DaemonResponseSender),
+    PrintWidgets {
+        json: bool,
+        sender: DaemonResponseSender,
+    },
+    PrintProcessOutput {
+        source: Option<String>,
+        sender: DaemonResponseSender,
+    },
+    PrintErrors {
+        json: bool,
+        sender: DaemonResponseSender,
+    },
+    DescribeRuntime(DaemonResponseSender),
+    StartRecording {
+        path: std::path::PathBuf,
+        sender: DaemonResponseSender,
+    },
+    StopRecording(DaemonResponseSender),
+    Replay {
+        path: std::path::PathBuf,
+        keep_timing: bool,
+        sender: DaemonResponseSender,
+    },
 }
 
 /// An opened window.
 #[derive(Debug)]
 pub struct EwwWindow {
+    /// Name of the window definition
     pub name: String,
+    /// Id of this window instance, see [`WindowArguments::instance_id`]
+    pub instance_id: String,
     pub scope_index: ScopeIndex,
     pub gtk_window: gtk::Window,
+    /// The widget the window definition contains, which is the only child of [`Self::gtk_window`]
+    pub root_widget: gtk::Widget,
     pub destroy_event_handler_id: Option<glib::SignalHandlerId>,
+    /// Geometry and monitor geometry the window is currently placed with.
+    /// This is shared with the event handler that keeps the window in place on x11.
+    pub placement: Rc<Cell<Option<(WindowGeometry, gdk::Rectangle)>>>,
+    /// Geometry of the monitor the window was opened on
+    pub monitor_geometry: gdk::Rectangle,
+    /// CSS classes that were set on the root widget via `eww set-class`
+    pub runtime_classes: Vec<String>,
+    /// Animation that is played when the window is opened and explicitly closed
+    pub animation: Option<SlideAnimation>,
 }
 
 impl EwwWindow {
@@ -91,27 +246,91 @@ impl EwwWindow {
     /// You need to make sure that the scope get's properly cleaned from the state graph
     /// and that script-vars get cleaned up properly
     pub fn close(self) {
-        log::info!("Closing gtk window {}", self.name);
+        log::info!("Closing gtk window {}", self.instance_id);
         self.gtk_window.close();
         if let Some(handler_id) = self.destroy_event_handler_id {
             self.gtk_window.disconnect(handler_id);
         }
     }
+
+    /// Like [`Self::close`], but slides the window out of its monitor first if it has an animation.
+    pub fn close_animated<B: DisplayBackend>(self) {
+        let animation = match self.animation {
+            Some(animation) => animation,
+            None => return self.close(),
+        };
+        log::info!("Closing gtk window {} after its animation", self.instance_id);
+        // Disconnect right away, as the window may only be destroyed once the animation is done
+        if let Some(handler_id) = self.destroy_event_handler_id {
+            self.gtk_window.disconnect(handler_id);
+        }
+        slide_window::<B>(&self.gtk_window, self.placement, animation, false, |window| window.close());
+    }
+}
+
+/// Commands that take longer than this to handle are logged as a warning, as they block the UI while they are handled.
+const SLOW_COMMAND_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// A client waiting for a global variable to satisfy a condition, see [`DaemonCommand::WaitForVar`].
+#[derive(Debug)]
+pub struct VarWaiter {
+    pub name: VarName,
+    pub predicate: SimplExpr,
+    /// The awaited variable and the variables used in the predicate, whose changes cause the predicate to be checked again
+    pub watched_vars: Vec<VarName>,
+    /// When the client stops waiting, if a timeout was given
+    pub deadline: Option<std::time::Instant>,
+    /// Set once the client has been responded to, shared with the timer responding when the timeout is reached
+    pub responded: Rc<Cell<bool>>,
+    pub sender: DaemonResponseSender,
+}
+
+impl VarWaiter {
+    /// Whether the client no longer waits for a response, because it already got one, timed out or disconnected.
+    fn is_done(&self, now: std::time::Instant) -> bool {
+        self.responded.get() || self.sender.is_closed() || self.deadline.map_or(false, |deadline| deadline <= now)
+    }
+}
+
+/// A client that gets sent the values of some global variables whenever they change, see [`DaemonCommand::Subscribe`].
+#[derive(Debug)]
+pub struct VarSubscriber {
+    pub vars: Vec<VarName>,
+    pub sender: DaemonResponseSender,
 }
 
 pub struct App<B> {
     pub display_backend: B,
     pub scope_graph: Rc<RefCell<ScopeGraph>>,
     pub eww_config: config::EwwConfig,
-    /// Map of all currently open windows
+    /// Map of all currently open windows, by their instance id
     pub open_windows: HashMap<String, EwwWindow>,
-    /// Window names that are supposed to be open, but failed.
+    /// The arguments that the open window instances where opened with, by their instance id
+    pub instance_id_to_args: HashMap<String, WindowArguments>,
+    /// Geometry of windows that was last given explicitly when opening or moving them, by instance id.
+    /// This is used instead of the geometry from the configuration when reopening these windows, until it is forgotten again.
+    pub saved_window_geometry: HashMap<String, WindowGeometry>,
+    /// Instance ids of windows that are supposed to be open, but failed.
     /// When reloading the config, these should be opened again.
     pub failed_windows: HashSet<String>,
+    /// Clients waiting for a variable to satisfy a condition.
+    pub var_waiters: Vec<VarWaiter>,
+    pub var_subscribers: Vec<VarSubscriber>,
+    /// Cancellation flags of the timers closing windows opened with a timeout, by instance id
+    pub close_timers: HashMap<String, Rc<Cell<bool>>>,
+    /// Whether the script-vars have been paused via [`DaemonCommand::PauseScriptVars`]
+    pub script_vars_paused: bool,
     pub css_provider: gtk::CssProvider,
+    /// Provider with a higher priority than [`Self::css_provider`], overriding its color definitions
+    /// when only those changed since the stylesheet was last fully loaded.
+    pub color_css_provider: gtk::CssProvider,
+    /// The stylesheet that is currently applied
+    pub loaded_stylesheet: Option<SplitStylesheet>,
 
     /// Sender to send [`DaemonCommand`]s
     pub app_evt_send: UnboundedSender<DaemonCommand>,
+    /// Sender to tell the file watcher which configuration directory to watch, see [`Self::switch_config`]
+    pub config_dir_send: tokio::sync::watch::Sender<std::path::PathBuf>,
     pub script_var_handler: ScriptVarHandlerHandle,
 
     pub paths: EwwPaths,
@@ -123,7 +342,13 @@ impl<B> std::fmt::Debug for App<B> {
             .field("scope_graph", &*self.scope_graph.borrow())
             .field("eww_config", &self.eww_config)
             .field("open_windows", &self.open_windows)
+            .field("instance_id_to_args", &self.instance_id_to_args)
+            .field("saved_window_geometry", &self.saved_window_geometry)
             .field("failed_windows", &self.failed_windows)
+            .field("var_waiters", &self.var_waiters)
+            .field("var_subscribers", &self.var_subscribers)
+            .field("close_timers", &self.close_timers.keys().sorted().collect::<Vec<_>>())
+            .field("script_vars_paused", &self.script_vars_paused)
             .field("paths", &self.paths)
             .finish()
     }
@@ -133,9 +358,12 @@ impl<B: DisplayBackend> App<B> {
     /// Handle a [`DaemonCommand`] event.
     pub fn handle_command(&mut self, event: DaemonCommand) {
         log::debug!("Handling event: {:?}", &event);
+        let command_name = get_command_name(&event);
+        let start_time = std::time::Instant::now();
         let result: Result<_> = try {
             match event {
                 DaemonCommand::NoOp => {}
+                DaemonCommand::Ping(sender) => sender.send_success("pong".to_owned())?,
                 DaemonCommand::OpenInspector => {
                     gtk::Window::set_interactive_debugging(true);
                 }
@@ -144,6 +372,14 @@ impl<B: DisplayBackend> App<B> {
                         self.update_global_variable(var_name, new_value);
                     }
                 }
+                DaemonCommand::SetVar { name, value, persist, sender } => {
+                    if !self.scope_graph.borrow().global_scope().data.contains_key(&name) {
+                        sender.send_failure_of_kind(ErrorKind::NoSuchVariable, format!("Variable not found \"{}\"", name))?
+                    } else {
+                        let result = self.set_variable(name, value, persist);
+                        sender.respond_with_result(result)?
+                    }
+                }
                 DaemonCommand::ReloadConfigAndCss(sender) => {
                     let mut errors = Vec::new();
 
@@ -151,19 +387,40 @@ impl<B: DisplayBackend> App<B> {
                     if let Err(e) = config_result.and_then(|new_config| self.load_config(new_config)) {
                         errors.push(e)
                     }
-                    match crate::config::scss::parse_scss_from_config(self.paths.get_config_dir()) {
-                        Ok((file_id, css)) => {
-                            if let Err(e) = self.load_css(file_id, &css) {
-                                errors.push(anyhow!(e));
-                            }
-                        }
-                        Err(e) => {
-                            errors.push(e);
-                        }
+                    if let Err(e) = self.reload_css() {
+                        errors.push(e);
                     }
 
                     sender.respond_with_error_list(errors)?;
                 }
+                DaemonCommand::ReloadCss(sender) => {
+                    sender.respond_with_result(self.reload_css())?;
+                }
+                DaemonCommand::ReloadConfig(sender) => {
+                    let config_result = config::read_from_eww_paths(&self.paths);
+                    sender.respond_with_result(config_result.and_then(|new_config| self.load_config(new_config)))?;
+                }
+                DaemonCommand::ReassertGeometry(sender) => {
+                    sender.respond_with_error_list(self.reassert_window_geometry())?;
+                }
+                DaemonCommand::MonitorsChanged => {
+                    for err in self.handle_monitors_changed() {
+                        error_handling_ctx::print_error(err);
+                    }
+                }
+                DaemonCommand::ForgetGeometry { windows, sender } => {
+                    if windows.is_empty() {
+                        self.saved_window_geometry.clear();
+                    } else {
+                        for instance_id in &windows {
+                            self.saved_window_geometry.remove(instance_id);
+                        }
+                    }
+                    sender.send_success(String::new())?
+                }
+                DaemonCommand::Redraw { windows, sender } => {
+                    sender.respond_with_error_list(self.redraw_windows(&windows))?;
+                }
                 DaemonCommand::KillServer => {
                     log::info!("Received kill command, stopping server!");
                     self.stop_application();
@@ -171,47 +428,144 @@ impl<B: DisplayBackend> App<B> {
                 DaemonCommand::CloseAll => {
                     log::info!("Received close command, closing all windows");
                     for window_name in self.open_windows.keys().cloned().collect::<Vec<String>>() {
-                        self.close_window(&window_name)?;
+                        self.close_window_if_open(&window_name);
                     }
                 }
-                DaemonCommand::OpenMany { windows, should_toggle, sender } => {
+                DaemonCommand::PauseScriptVars => {
+                    self.script_var_handler.pause();
+                    self.script_vars_paused = true;
+                }
+                DaemonCommand::ResumeScriptVars => {
+                    self.script_var_handler.resume();
+                    self.script_vars_paused = false;
+                }
+                DaemonCommand::RestartScriptVar { name, sender } => match self.eww_config.get_script_var(&name) {
+                    Ok(_) => {
+                        // Script-vars that aren't used by any window or whose run-while condition is false aren't running,
+                        // and should stay that way, so the handler only restarts the ones that are running.
+                        self.script_var_handler.restart(name);
+                        sender.send_success(String::new())?
+                    }
+                    Err(e) => sender.send_failure_of_kind(ErrorKind::NoSuchVariable, e.to_string())?,
+                },
+                DaemonCommand::OpenMany { windows, should_toggle, if_open, sender } => {
                     let errors = windows
                         .iter()
-                        .map(|w| {
-                            if should_toggle && self.open_windows.contains_key(w) {
-                                self.close_window(w)
-                            } else {
-                                self.open_window(w, None, None, None, None)
-                            }
-                        })
+                        .map(|w| self.open_or_toggle_window(&WindowArguments::new_from_name(w.clone()), should_toggle, if_open))
                         .filter_map(Result::err);
                     sender.respond_with_error_list(errors)?;
                 }
-                DaemonCommand::OpenWindow { window_name, pos, size, anchor, screen: monitor, should_toggle, sender } => {
-                    let is_open = self.open_windows.contains_key(&window_name);
-                    let result = if !is_open {
-                        self.open_window(&window_name, pos, size, monitor, anchor)
-                    } else if should_toggle {
-                        self.close_window(&window_name)
+                DaemonCommand::OpenWindow {
+                    window_name,
+                    instance_id,
+                    pos,
+                    size,
+                    anchor,
+                    close_after,
+                    relative_to,
+                    screen: monitor,
+                    all_monitors,
+                    should_toggle,
+                    if_open,
+                    args,
+                    sender,
+                } => {
+                    let mut window_args = WindowArguments {
+                        instance_id: instance_id.unwrap_or_else(|| window_name.clone()),
+                        window_name,
+                        anchor,
+                        monitor,
+                        pos: None,
+                        size,
+                        close_after,
+                        // Positions at the cursor or relative to another window are only meant for this one opening
+                        remember_geometry: relative_to.is_none() && !matches!(pos, Some(WindowPosition::Cursor)),
+                        args: args.into_iter().collect(),
+                    };
+                    let position_result = match (pos, relative_to) {
+                        (Some(WindowPosition::Cursor), Some(_)) => {
+                            Err(anyhow!("A window can't be opened at the cursor and relative to another window at the same time"))
+                        }
+                        (Some(WindowPosition::Cursor), None) => self.place_at_cursor(&mut window_args),
+                        (pos, relative_to) => {
+                            window_args.pos = match pos {
+                                Some(WindowPosition::Coords(pos)) => Some(pos),
+                                _ => None,
+                            };
+                            match relative_to {
+                                Some(other_instance_id) => self.place_relative_to(&mut window_args, &other_instance_id),
+                                None => Ok(()),
+                            }
+                        }
+                    };
+                    if let Err(err) = position_result {
+                        sender.respond_with_result::<()>(Err(err))?;
+                    } else if all_monitors {
+                        let instances = instances_on_all_monitors(&window_args);
+                        let errors = instances
+                            .iter()
+                            .map(|window_args| self.open_or_toggle_window(window_args, should_toggle, if_open))
+                            .filter_map(Result::err);
+                        sender.respond_with_error_list(errors)?;
                     } else {
-                        Ok(())
+                        let result = self.open_or_toggle_window(&window_args, should_toggle, if_open);
+                        sender.respond_with_result(result)?;
+                    }
+                }
+                DaemonCommand::GetWindowGeometry { window_name, pos, size, anchor, screen, sender } => {
+                    let result: Result<_> = try {
+                        // Resolve the geometry the same way opening the window would, such that the result matches it exactly.
+                        let window_args = WindowArguments {
+                            anchor,
+                            monitor: screen,
+                            pos,
+                            size,
+                            ..WindowArguments::new_from_name(window_name.clone())
+                        };
+                        let window_def = self.get_window_definition_for(&window_args)?;
+                        let geometry = window_def.geometry.with_context(|| {
+                            format!("Window '{}' has no geometry, its size and position depend on its content", window_name)
+                        })?;
+                        let monitor_geometry = get_monitor_geometry(window_def.monitor)?;
+                        let rect = with_minimum_window_size(get_window_rectangle(geometry, monitor_geometry));
+                        format!("{}x{}{:+}{:+}", rect.width(), rect.height(), rect.x(), rect.y())
                     };
-                    sender.respond_with_result(result)?;
+                    match result {
+                        Ok(output) => sender.send_success(output)?,
+                        Err(e) => sender.respond_with_result::<()>(Err(e))?,
+                    }
+                }
+                DaemonCommand::MoveWindow { instance_id, pos, size, anchor, sender } => {
+                    sender.respond_with_result(self.move_window(&instance_id, pos, size, anchor))?;
+                }
+                DaemonCommand::SetWindowClass { instance_id, classes, sender } => {
+                    sender.respond_with_result(self.set_window_classes(&instance_id, classes))?;
                 }
                 DaemonCommand::CloseWindows { windows, sender } => {
-                    let errors = windows.iter().map(|window| self.close_window(window)).filter_map(Result::err);
+                    let errors =
+                        windows.iter().map(|window| self.close_window_or_monitor_instances(window)).filter_map(Result::err);
                     sender.respond_with_error_list(errors)?;
                 }
-                DaemonCommand::PrintState { all, sender } => {
+                DaemonCommand::SwitchConfig { config_dir, windows, sender } => match self.switch_config(&config_dir, &windows) {
+                    Ok(()) => sender
+                        .send_success(format!("Switched to the configuration in {}", self.paths.get_config_dir().display()))?,
+                    Err(e) => sender.respond_with_result::<()>(Err(e))?,
+                },
+                DaemonCommand::FocusWindow { previous, sender } => {
+                    sender.respond_with_result(self.focus_next_window(previous))?;
+                }
+                DaemonCommand::PrintState { all, json, sender } => {
                     let scope_graph = self.scope_graph.borrow();
                     let used_globals_names = scope_graph.currently_used_globals();
-                    let output = scope_graph
-                        .global_scope()
-                        .data
-                        .iter()
-                        .filter(|(key, _)| all || used_globals_names.contains(*key))
-                        .map(|(key, value)| format!("{}: {}", key, value))
-                        .join("\n");
+                    let vars = scope_graph.global_scope().data.iter().filter(|(key, _)| all || used_globals_names.contains(*key));
+                    let output = if json {
+                        let vars = vars
+                            .map(|(key, value)| (key.to_string(), serde_json::Value::String(value.to_string())))
+                            .collect::<serde_json::Map<_, _>>();
+                        serde_json::to_string(&vars)?
+                    } else {
+                        vars.map(|(key, value)| format!("{}: {}", key, value)).join("\n")
+                    };
                     sender.send_success(output)?
                 }
                 DaemonCommand::GetVar { name, sender } => {
@@ -219,31 +573,213 @@ impl<B: DisplayBackend> App<B> {
                     let vars = &scope_graph.global_scope().data;
                     match vars.get(name.as_str()) {
                         Some(x) => sender.send_success(x.to_string())?,
-                        None => sender.send_failure(format!("Variable not found \"{}\"", name))?,
+                        None => {
+                            sender.send_failure_of_kind(ErrorKind::NoSuchVariable, format!("Variable not found \"{}\"", name))?
+                        }
+                    }
+                }
+                DaemonCommand::GetWidgetAttribute { window_id, widget_id, attribute, sender } => {
+                    match self.get_widget_attribute_value(&window_id, &widget_id, &attribute) {
+                        Ok(value) => sender.send_success(value.to_string())?,
+                        Err(e) => sender.respond_with_result::<()>(Err(e))?,
+                    }
+                }
+                DaemonCommand::InspectWindow { window_id, sender } => match self.open_windows.get(&window_id) {
+                    Some(window) => {
+                        let mut output = String::new();
+                        format_widget_tree(&window.root_widget, 0, &mut output);
+                        sender.send_success(output.trim_end().to_string())?
+                    }
+                    None => sender.respond_with_result::<()>(Err(window_not_open_error("inspect", &window_id)))?,
+                },
+                DaemonCommand::Screenshot { window_id, path, sender } => {
+                    sender.respond_with_result(self.screenshot_window(&window_id, &path))?;
+                }
+                DaemonCommand::GetWindowMonitor { window_id, sender } => {
+                    let result = self.get_window_monitor(&window_id);
+                    match result {
+                        Ok((index, name)) => {
+                            sender.send_success(format!("{} {}", index, name.as_deref().unwrap_or("unknown")))?
+                        }
+                        Err(e) => sender.respond_with_result::<()>(Err(e))?,
+                    }
+                }
+                DaemonCommand::Subscribe { vars, sender } => self.subscribe(vars, sender)?,
+                DaemonCommand::WaitForVar { name, predicate, timeout, sender } => {
+                    self.wait_for_var(name, &predicate, timeout, sender)?;
+                }
+                DaemonCommand::PrintWindows { json, sender } => {
+                    let windows = self.eww_config.get_windows().keys().map(|window_name| {
+                        let is_open = self.open_windows.values().any(|window| &window.name == window_name);
+                        (window_name, is_open)
+                    });
+                    let output = if json {
+                        let windows = windows
+                            .sorted()
+                            .map(|(name, is_open)| serde_json::json!({ "name": name, "open": is_open }))
+                            .collect::<Vec<_>>();
+                        serde_json::to_string(&windows)?
+                    } else {
+                        windows
+                            .map(|(window_name, is_open)| format!("{}{}", if is_open { "*" } else { "" }, window_name))
+                            .join("\n")
+                    };
+                    sender.send_success(output)?
+                }
+                DaemonCommand::IsWindowOpen { window_id, sender } => {
+                    // Instances with a custom id are only known while they are open, or failed to open.
+                    let is_known =
+                        self.instance_id_to_args.contains_key(&window_id) || self.eww_config.get_window(&window_id).is_ok();
+                    if self.open_windows.contains_key(&window_id) {
+                        sender.send_success("true".to_string())?
+                    } else if is_known {
+                        sender.send_success("false".to_string())?
+                    } else {
+                        let message = format!("No window with the id `{}` exists", window_id);
+                        sender.send_failure_of_kind(ErrorKind::NoSuchWindow, message)?
                     }
                 }
-                DaemonCommand::PrintWindows(sender) => {
-                    let output = self
-                        .eww_config
-                        .get_windows()
-                        .keys()
-                        .map(|window_name| {
-                            let is_open = self.open_windows.contains_key(window_name);
-                            format!("{}{}", if is_open { "*" } else { "" }, window_name)
-                        })
-                        .join("\n");
+                DaemonCommand::ListWindowsWithGeometry { json, sender } => {
+                    let windows = self.open_windows.values().sorted_by_key(|window| &window.instance_id).map(|window| {
+                        (window, get_monitor_index(window.monitor_geometry), get_current_window_rectangle(&window.gtk_window))
+                    });
+                    let output = if json {
+                        let windows = windows
+                            .map(|(window, monitor, rect)| {
+                                serde_json::json!({
+                                    "id": window.instance_id,
+                                    "name": window.name,
+                                    "monitor": monitor,
+                                    "x": rect.x(),
+                                    "y": rect.y(),
+                                    "width": rect.width(),
+                                    "height": rect.height(),
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        serde_json::to_string(&windows)?
+                    } else {
+                        windows
+                            .map(|(window, monitor, rect)| {
+                                let monitor = monitor.map_or_else(|| "unknown".to_string(), |monitor| monitor.to_string());
+                                format!(
+                                    "{} ({}) on monitor {}: {}x{}{:+}{:+}",
+                                    window.instance_id,
+                                    window.name,
+                                    monitor,
+                                    rect.width(),
+                                    rect.height(),
+                                    rect.x(),
+                                    rect.y()
+                                )
+                            })
+                            .join("\n")
+                    };
+                    sender.send_success(output)?
+                }
+                DaemonCommand::PrintActiveVars { json, sender } => {
+                    let active_vars = self.active_variables();
+                    let output = if json {
+                        let vars = active_vars
+                            .iter()
+                            .map(|(name, windows)| {
+                                let is_script_var = self.eww_config.get_script_var(name).is_ok();
+                                serde_json::json!({ "name": name, "script_var": is_script_var, "windows": windows })
+                            })
+                            .collect::<Vec<_>>();
+                        serde_json::to_string(&vars)?
+                    } else {
+                        active_vars
+                            .iter()
+                            .map(|(name, windows)| {
+                                let marker = if self.eww_config.get_script_var(name).is_ok() { "*" } else { "" };
+                                format!("{}{}: {}", marker, name, windows.join(", "))
+                            })
+                            .join("\n")
+                    };
                     sender.send_success(output)?
                 }
                 DaemonCommand::PrintDebug(sender) => {
                     let output = format!("{:#?}", &self);
                     sender.send_success(output)?
                 }
+                DaemonCommand::DumpConfig(sender) => {
+                    // Going through a json value sorts the keys, keeping the output stable.
+                    let config = serde_json::to_value(&self.eww_config)?;
+                    sender.send_success(serde_json::to_string_pretty(&config)?)?
+                }
                 DaemonCommand::PrintGraph(sender) => sender.send_success(self.scope_graph.borrow().visualize())?,
+                DaemonCommand::PrintWidgets { json, sender } => {
+                    let widgets = self.eww_config.get_widget_definitions().values().sorted_by(|a, b| a.name.cmp(&b.name));
+                    let output = if json {
+                        let widgets = widgets
+                            .map(|widget| {
+                                let args = widget
+                                    .expected_args
+                                    .iter()
+                                    .map(|arg| serde_json::json!({ "name": arg.name.0, "optional": arg.optional }))
+                                    .collect::<Vec<_>>();
+                                serde_json::json!({ "name": widget.name, "args": args })
+                            })
+                            .collect::<Vec<_>>();
+                        serde_json::to_string(&widgets)?
+                    } else {
+                        widgets
+                            .map(|widget| {
+                                let args = widget.expected_args.iter().map(|arg| {
+                                    if arg.optional {
+                                        format!("?{}", arg.name)
+                                    } else {
+                                        arg.name.to_string()
+                                    }
+                                });
+                                std::iter::once(widget.name.clone()).chain(args).join(" ")
+                            })
+                            .join("\n")
+                    };
+                    sender.send_success(output)?
+                }
+                DaemonCommand::PrintBuiltinWidgets(sender) => {
+                    sender.send_success(crate::widgets::widget_docs::format_builtin_widget_docs())?
+                }
+                DaemonCommand::PrintProcessOutput { source, sender } => {
+                    sender.send_success(crate::process_output::format_buffered_output(source.as_deref()))?
+                }
+                DaemonCommand::DescribeRuntime(sender) => {
+                    sender.send_success(serde_json::to_string_pretty(&self.describe_runtime())?)?;
+                }
+                DaemonCommand::PrintErrors { json, sender } => {
+                    let error_history = error_handling_ctx::error_history();
+                    let output = if json {
+                        serde_json::to_string(&error_history.iter().map(ErrorHistoryEntry::to_json).collect::<Vec<_>>())?
+                    } else {
+                        error_history.iter().join("\n")
+                    };
+                    sender.send_success(output)?
+                }
+                DaemonCommand::StartRecording { path, sender } => {
+                    sender.respond_with_result(command_recording::start(&path))?;
+                }
+                DaemonCommand::StopRecording(sender) => match command_recording::stop() {
+                    Ok(path) => sender.send_success(format!("Recording saved to {}", path.display()))?,
+                    Err(err) => sender.respond_with_result::<()>(Err(err))?,
+                },
+                DaemonCommand::Replay { path, keep_timing, sender } => match command_recording::read_recording(&path) {
+                    Ok(actions) => self.replay_actions(actions, keep_timing, sender)?,
+                    Err(err) => sender.respond_with_result::<()>(Err(err))?,
+                },
             }
         };
 
+        let duration = start_time.elapsed();
+        if duration >= SLOW_COMMAND_THRESHOLD {
+            log::warn!("Handling {} took {}ms", command_name, duration.as_millis());
+        } else {
+            log::debug!("Handling {} took {}ms", command_name, duration.as_millis());
+        }
+
         if let Err(err) = result {
-            error_handling_ctx::print_error(err);
+            error_handling_ctx::print_error_from(command_name, err);
         }
     }
 
@@ -265,6 +801,151 @@ impl<B: DisplayBackend> App<B> {
         }
 
         self.apply_run_while_expressions_mentioning(&name);
+        self.apply_close_when_conditions_mentioning(&name);
+        self.respond_to_var_waiters_of(&name);
+        self.notify_var_subscribers_of(&name);
+        self.update_derived_variables_mentioning(&name);
+    }
+
+    /// Set the value of a global variable, optionally persisting it such that it is kept when the daemon restarts.
+    /// Values of script-vars and derived variables can't be persisted, as they are computed by eww itself.
+    fn set_variable(&mut self, name: VarName, value: DynVal, persist: bool) -> Result<()> {
+        if persist {
+            if !self.eww_config.get_initial_variables().contains_key(&name) {
+                bail!("Only variables defined via defvar can be persisted, but \"{}\" is a defpoll, deflisten or defexpr", name);
+            }
+            persisted_vars::persist(self.paths.get_persisted_vars_file(), &name, &value)?;
+        }
+        self.update_global_variable(name, value);
+        Ok(())
+    }
+
+    /// Recompute the derived variables whose expression references the given variable.
+    /// As cycles between derived variables are rejected when loading the configuration, this always terminates.
+    fn update_derived_variables_mentioning(&mut self, name: &VarName) {
+        let derived_vars = match self.eww_config.get_derived_var_mentions_of(name) {
+            Some(x) => x.clone(),
+            None => return,
+        };
+        for derived_var in derived_vars {
+            let value = self.eww_config.get_derived_var(&derived_var).and_then(|var| {
+                let scope_graph = self.scope_graph.borrow();
+                scope_graph.evaluate_simplexpr_in_scope(scope_graph.root_index, &var.expr)
+            });
+            match value {
+                Ok(value) => self.update_global_variable(derived_var, value),
+                Err(err) => error_handling_ctx::print_error(err),
+            }
+        }
+    }
+
+    /// Windows may define a `:close-when` condition.
+    /// When a variable changes, the conditions of all open windows that mention the variable are reevaluated,
+    /// and the windows whose condition became true are closed.
+    fn apply_close_when_conditions_mentioning(&mut self, name: &VarName) {
+        let scope_graph = self.scope_graph.borrow();
+        let windows_to_close: Vec<String> = self
+            .open_windows
+            .values()
+            .filter_map(|window| {
+                let close_when = self.eww_config.get_window(&window.name).ok()?.close_when.as_ref()?;
+                if !close_when.collect_var_refs().contains(name) {
+                    return None;
+                }
+                let close_when_result =
+                    scope_graph.evaluate_simplexpr_in_scope(window.scope_index, close_when).and_then(|v| Ok(v.as_bool()?));
+                match close_when_result {
+                    Ok(true) => Some(window.instance_id.clone()),
+                    Ok(false) => None,
+                    Err(err) => {
+                        error_handling_ctx::print_error(err);
+                        None
+                    }
+                }
+            })
+            .collect();
+        drop(scope_graph);
+
+        for instance_id in windows_to_close {
+            log::info!("Closing window {}, as its :close-when condition became true", instance_id);
+            self.close_window_if_open(&instance_id);
+        }
+    }
+
+    /// Register a [`VarWaiter`] for the given variable, or respond right away if the condition already holds.
+    fn wait_for_var(
+        &mut self,
+        name: VarName,
+        predicate: &str,
+        timeout: Option<Duration>,
+        sender: DaemonResponseSender,
+    ) -> Result<()> {
+        if !self.scope_graph.borrow().global_scope().data.contains_key(&name) {
+            return sender.send_failure_of_kind(ErrorKind::NoSuchVariable, format!("Variable not found \"{}\"", name));
+        }
+        let predicate = match parse_simplexpr_from_cli("<wait condition>", predicate) {
+            Ok(predicate) => predicate,
+            Err(err) => return sender.respond_with_result::<()>(Err(err)),
+        };
+        let deadline = timeout.map(|timeout| std::time::Instant::now() + timeout);
+        let mut watched_vars = predicate.collect_var_refs();
+        watched_vars.push(name.clone());
+        let waiter = VarWaiter { name, predicate, watched_vars, deadline, responded: Rc::new(Cell::new(false)), sender };
+        if try_respond_to_var_waiter(&self.scope_graph.borrow(), &waiter) {
+            return Ok(());
+        }
+        if let Some(timeout) = timeout {
+            let responded = waiter.responded.clone();
+            let sender = waiter.sender.clone();
+            let name = waiter.name.clone();
+            glib::timeout_add_local_once(timeout, move || {
+                if !responded.replace(true) {
+                    let _ = sender.send_failure(format!("Timed out waiting for variable \"{}\"", name));
+                }
+            });
+        }
+        // Waiters on variables that never change are only dropped here, so they don't pile up.
+        let now = std::time::Instant::now();
+        self.var_waiters.retain(|waiter| !waiter.is_done(now));
+        self.var_waiters.push(waiter);
+        Ok(())
+    }
+
+    /// Send the current values of the given variables to a client, and keep sending them whenever they change.
+    fn subscribe(&mut self, vars: Vec<VarName>, sender: DaemonResponseSender) -> Result<()> {
+        let scope_graph = self.scope_graph.borrow();
+        let globals = &scope_graph.global_scope().data;
+        if let Some(unknown) = vars.iter().find(|name| !globals.contains_key(*name)) {
+            return sender.send_failure_of_kind(ErrorKind::NoSuchVariable, format!("Variable not found \"{}\"", unknown));
+        }
+        for name in &vars {
+            sender.send_success(format!("{}: {}", name, globals[name]))?;
+        }
+        drop(scope_graph);
+        self.var_subscribers.push(VarSubscriber { vars, sender });
+        Ok(())
+    }
+
+    /// Send the new value of a variable to all clients subscribed to it, dropping clients that stopped listening.
+    fn notify_var_subscribers_of(&mut self, name: &VarName) {
+        let scope_graph = self.scope_graph.borrow();
+        let value = match scope_graph.global_scope().data.get(name) {
+            Some(value) => value,
+            None => return,
+        };
+        self.var_subscribers.retain(|subscriber| {
+            !subscriber.vars.contains(name) || subscriber.sender.send_success(format!("{}: {}", name, value)).is_ok()
+        });
+    }
+
+    /// Re-check the conditions of all [`VarWaiter`]s that watch the given variable,
+    /// removing the ones that have been responded to, timed out or whose client stopped waiting.
+    fn respond_to_var_waiters_of(&mut self, name: &VarName) {
+        let scope_graph = self.scope_graph.borrow();
+        let now = std::time::Instant::now();
+        self.var_waiters.retain(|waiter| {
+            !waiter.is_done(now) && (!waiter.watched_vars.contains(name) || !try_respond_to_var_waiter(&scope_graph, waiter))
+        });
     }
 
     /// Variables may be referenced in defpoll :run-while expressions.
@@ -292,55 +973,510 @@ impl<B: DisplayBackend> App<B> {
         }
     }
 
-    /// Close a window and do all the required cleanups in the scope_graph and script_var_handler
-    fn close_window(&mut self, window_name: &str) -> Result<()> {
-        let eww_window = self
+    /// Describe everything the daemon is currently doing: open windows, running script-vars,
+    /// listeners in the scope graph, and clients waiting for variables.
+    fn describe_runtime(&self) -> serde_json::Value {
+        let windows = self
+            .open_windows
+            .iter()
+            .sorted_by_key(|(instance_id, _)| *instance_id)
+            .map(|(instance_id, window)| {
+                let close_when = self.eww_config.get_window(&window.name).ok().and_then(|def| def.close_when.as_ref());
+                serde_json::json!({
+                    "id": instance_id,
+                    "window": window.name,
+                    "scope_index": window.scope_index.0,
+                    "close_when": close_when.map(|expr| expr.to_string()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let scope_graph = self.scope_graph.borrow();
+        let running_script_vars = scope_graph
+            .currently_used_globals()
+            .into_iter()
+            .filter(|name| self.eww_config.get_script_var(name).is_ok())
+            .map(|name| name.to_string())
+            .sorted()
+            .collect::<Vec<_>>();
+
+        let now = std::time::Instant::now();
+        let var_waiters = self
+            .var_waiters
+            .iter()
+            .filter(|waiter| !waiter.is_done(now))
+            .map(|waiter| {
+                serde_json::json!({
+                    "variable": waiter.name.to_string(),
+                    "condition": waiter.predicate.to_string(),
+                    "remaining_timeout_ms": waiter
+                        .deadline
+                        .map(|deadline| deadline.saturating_duration_since(now).as_millis() as u64),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "windows": windows,
+            "failed_windows": self.failed_windows.iter().sorted().collect::<Vec<_>>(),
+            "script_vars": {
+                "paused": self.script_vars_paused,
+                "running": running_script_vars,
+            },
+            "listeners": scope_graph.describe_listeners(),
+            "var_waiters": var_waiters,
+        })
+    }
+
+    /// Evaluate an attribute of a widget that was given an id, in the scope that widget is used in.
+    fn get_widget_attribute_value(&self, window_id: &str, widget_id: &str, attribute: &str) -> Result<DynVal> {
+        let window = self
+            .open_windows
+            .get(window_id)
+            .ok_or_else(|| ErrorKind::NoSuchWindow.error(format!("No window with id '{}' is open", window_id)))?;
+        let scope_graph = self.scope_graph.borrow();
+        let (scope_index, attributes) = scope_graph
+            .find_widget_attributes(window.scope_index, widget_id)
+            .with_context(|| format!("No widget with id '{}' in window '{}'", widget_id, window_id))?;
+        let entry = attributes
+            .get(&AttrName(attribute.to_string()))
+            .with_context(|| format!("Widget '{}' has no attribute '{}'", widget_id, attribute))?;
+        let expr = SimplExpr::from_ast(entry.value.clone())?;
+        scope_graph.evaluate_simplexpr_in_scope(scope_index, &expr)
+    }
+
+    /// Focus the open window following the currently focused one, skipping windows that can't be focused.
+    /// Windows are ordered by their instance id, wrapping around at the end.
+    fn focus_next_window(&self, previous: bool) -> Result<()> {
+        let mut focusable_windows = self
             .open_windows
-            .remove(window_name)
-            .with_context(|| format!("Tried to close window named '{}', but no such window was open", window_name))?;
+            .iter()
+            .filter(|(_, window)| {
+                window.gtk_window.accepts_focus() && self.eww_config.get_window(&window.name).map_or(false, B::is_focusable)
+            })
+            .sorted_by_key(|(instance_id, _)| *instance_id)
+            .collect::<Vec<_>>();
+        if previous {
+            focusable_windows.reverse();
+        }
+        if focusable_windows.is_empty() {
+            return Err(anyhow!("There are no open windows that can be focused"));
+        }
+
+        let next_index = match focusable_windows.iter().position(|(_, window)| window.gtk_window.is_active()) {
+            Some(index) => (index + 1) % focusable_windows.len(),
+            None => 0,
+        };
+        let (instance_id, window) = focusable_windows[next_index];
+        log::debug!("Focusing window {}", instance_id);
+        window.gtk_window.present();
+        Ok(())
+    }
+
+    /// Get the definition of a window, with the geometry and monitor it gets placed with when opened with the given arguments.
+    fn get_window_definition_for(&self, window_args: &WindowArguments) -> Result<WindowDefinition> {
+        let mut window_def = self.eww_config.get_window(&window_args.window_name)?.clone();
+        let base_geometry = self.saved_window_geometry.get(&window_args.instance_id).copied().or(window_def.geometry);
+        window_def.geometry = base_geometry.map(|x| x.override_if_given(window_args.anchor, window_args.pos, window_args.size));
+        // Store the monitor that is actually used in the definition, such that the display backend places the window there
+        window_def.monitor =
+            window_args.monitor.clone().or_else(|| window_def.monitor.clone()).or_else(get_default_monitor_from_env);
+        // If the monitor can't be resolved, the window can't be opened anyways, so leave reporting that to the caller.
+        if let (Some(geometry), false) = (window_def.geometry, window_def.allow_offscreen) {
+            if let Ok(monitor_geometry) = get_monitor_geometry(window_def.monitor.clone()) {
+                if let Some(clamped) = clamp_geometry_to_monitor(geometry, monitor_geometry) {
+                    log::warn!("Window '{}' would be placed outside of its monitor, moving it onto it", window_args.instance_id);
+                    window_def.geometry = Some(clamped);
+                }
+            }
+        }
+        Ok(window_def)
+    }
+
+    /// Queue a redraw of the given open windows, or of all open windows if none are given.
+    fn redraw_windows(&self, instance_ids: &[String]) -> Vec<anyhow::Error> {
+        if instance_ids.is_empty() {
+            self.open_windows.values().for_each(|window| window.gtk_window.queue_draw());
+            return Vec::new();
+        }
+        instance_ids
+            .iter()
+            .filter_map(|instance_id| match self.open_windows.get(instance_id) {
+                Some(window) => {
+                    window.gtk_window.queue_draw();
+                    None
+                }
+                None => Some(window_not_open_error("redraw", instance_id)),
+            })
+            .collect()
+    }
+
+    /// Get the index and name of the monitor an open window is currently displayed on, as reported by gdk.
+    fn get_window_monitor(&self, instance_id: &str) -> Result<(i32, Option<String>)> {
+        let window =
+            self.open_windows.get(instance_id).ok_or_else(|| window_not_open_error("get the monitor of", instance_id))?;
+        let display = window.gtk_window.display();
+        let gdk_window = window.gtk_window.window().context("Failed to get gdk window from gtk window")?;
+        let monitor = display.monitor_at_window(&gdk_window).context("Failed to get the monitor of the window")?;
+        let index = (0..display.n_monitors())
+            .find(|index| display.monitor(*index).as_ref() == Some(&monitor))
+            .context("The monitor of the window is not known to the display")?;
+        Ok((index, monitor.model().map(|model| model.to_string())))
+    }
+
+    /// Render the contents of an open window and save them as a PNG image.
+    fn screenshot_window(&self, instance_id: &str, path: &std::path::Path) -> Result<()> {
+        let window =
+            self.open_windows.get(instance_id).ok_or_else(|| window_not_open_error("take a screenshot of", instance_id))?;
+        let (width, height) = (window.gtk_window.allocated_width(), window.gtk_window.allocated_height());
+        if !window.gtk_window.is_visible() || width <= 0 || height <= 0 {
+            bail!("Window {} is not visible, so it can't be rendered", instance_id);
+        }
+        let surface =
+            cairo::ImageSurface::create(cairo::Format::ARgb32, width, height).context("Failed to create image surface")?;
+        {
+            let cr = cairo::Context::new(&surface).context("Failed to create drawing context")?;
+            window.gtk_window.draw(&cr);
+        }
+        let pixbuf = gdk::pixbuf_get_from_surface(&surface, 0, 0, width, height)
+            .context("Rendering the window to an image is not supported on this backend")?;
+        pixbuf.savev(path, "png", &[]).with_context(|| format!("Failed to save screenshot to {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Replace the classes previously set on the root widget of a window with the given ones.
+    fn set_window_classes(&mut self, instance_id: &str, classes: Vec<String>) -> Result<()> {
+        let window =
+            self.open_windows.get_mut(instance_id).ok_or_else(|| window_not_open_error("set the classes of", instance_id))?;
+        let style_context = window.root_widget.style_context();
+        for class in &window.runtime_classes {
+            style_context.remove_class(class);
+        }
+        for class in &classes {
+            style_context.add_class(class);
+        }
+        window.runtime_classes = classes;
+        Ok(())
+    }
+
+    /// Apply the size, position and stacking of all open windows again,
+    /// i.e. to recover from the window manager moving or resizing them.
+    fn reassert_window_geometry(&self) -> Vec<anyhow::Error> {
+        self.open_windows
+            .iter()
+            .map(|(instance_id, window)| -> Result<()> {
+                let window_args = self
+                    .instance_id_to_args
+                    .get(instance_id)
+                    .with_context(|| format!("Missing arguments of open window '{}'", instance_id))?;
+                let window_def = self.get_window_definition_for(window_args)?;
+                let monitor_geometry = get_monitor_geometry(window_def.monitor.clone())?;
+                apply_window_placement(&window.gtk_window, &window_def, monitor_geometry)
+                    .with_context(|| format!("Failed to reapply geometry of window '{}'", instance_id))
+            })
+            .filter_map(Result::err)
+            .collect()
+    }
+
+    /// Set the position of a window such that its anchor point is at the mouse cursor.
+    /// Unless a monitor is given, the window is opened on the monitor the cursor is on.
+    fn place_at_cursor(&self, window_args: &mut WindowArguments) -> Result<()> {
+        let display = gdk::Display::default().context("Failed to get the default display")?;
+        let pointer = display.default_seat().and_then(|seat| seat.pointer()).context("Failed to get the mouse pointer")?;
+        let (_, cursor_x, cursor_y) = pointer.position();
+        if window_args.monitor.is_none() {
+            if let Some(cursor_monitor) = display.monitor_at_point(cursor_x, cursor_y) {
+                let monitor_index =
+                    (0..display.n_monitors()).find(|index| display.monitor(*index).as_ref() == Some(&cursor_monitor));
+                window_args.monitor = monitor_index.map(MonitorIdentifier::Numeric);
+            }
+        }
+        let window_def = self.get_window_definition_for(window_args)?;
+        let anchor = window_def.geometry.map(|geometry| geometry.anchor_point).unwrap_or_default();
+        let monitor_geometry = get_monitor_geometry(window_def.monitor)?;
+        let x = cursor_x - monitor_geometry.x() - anchor.x.alignment_to_coordinate(0, monitor_geometry.width());
+        let y = cursor_y - monitor_geometry.y() - anchor.y.alignment_to_coordinate(0, monitor_geometry.height());
+        window_args.pos = Some(Coords::from_pixels((x, y)));
+        Ok(())
+    }
+
+    /// Place a window relative to another open window.
+    /// The anchor, offset and size of the window are resolved within the rectangle of the other window instead of the monitor,
+    /// and the window opens on the monitor of the other window unless a monitor is given.
+    fn place_relative_to(&self, window_args: &mut WindowArguments, other_instance_id: &str) -> Result<()> {
+        let other_window = self
+            .open_windows
+            .get(other_instance_id)
+            .ok_or_else(|| window_not_open_error("place a window relative to", other_instance_id))?;
+        let other_rect = match other_window.placement.get() {
+            Some((geometry, monitor_geometry)) => get_window_rectangle(geometry, monitor_geometry),
+            None => get_current_window_rectangle(&other_window.gtk_window),
+        };
+        if window_args.monitor.is_none() {
+            window_args.monitor = get_monitor_index(other_window.monitor_geometry).map(MonitorIdentifier::Numeric);
+        }
+
+        let window_def = self.get_window_definition_for(window_args)?;
+        let geometry = window_def.geometry.with_context(|| {
+            format!("Window '{}' has no geometry, so it can't be placed relative to another window", window_args.window_name)
+        })?;
+        let monitor_geometry = get_monitor_geometry(window_def.monitor)?;
+        let rect = get_window_rectangle(geometry, other_rect);
+        let anchor = geometry.anchor_point;
+        let x = rect.x() - monitor_geometry.x() - anchor.x.alignment_to_coordinate(rect.width(), monitor_geometry.width());
+        let y = rect.y() - monitor_geometry.y() - anchor.y.alignment_to_coordinate(rect.height(), monitor_geometry.height());
+        window_args.pos = Some(Coords::from_pixels((x, y)));
+        window_args.size = Some(Coords::from_pixels((rect.width(), rect.height())));
+        Ok(())
+    }
+
+    /// Reopen the windows whose monitor changed, and close the ones whose monitor is gone.
+    /// Windows closed that way are kept as failed windows, and are opened again once their monitor is available.
+    fn handle_monitors_changed(&mut self) -> Vec<anyhow::Error> {
+        let instance_ids = self.open_windows.keys().chain(self.failed_windows.iter()).cloned().sorted().collect::<Vec<_>>();
+        instance_ids
+            .into_iter()
+            .filter_map(|instance_id| {
+                let window_args = self.instance_id_to_args.get(&instance_id)?.clone();
+                let window_def = self.get_window_definition_for(&window_args).ok()?;
+                let current_monitor_geometry = self.open_windows.get(&instance_id).map(|window| window.monitor_geometry);
+                match (get_monitor_geometry(window_def.monitor), current_monitor_geometry) {
+                    (Ok(monitor_geometry), Some(current)) if monitor_geometry == current => None,
+                    (Ok(_), _) => {
+                        log::info!("Monitor of window '{}' changed, reopening it", instance_id);
+                        self.open_window(&window_args).err()
+                    }
+                    (Err(_), Some(_)) => {
+                        log::warn!("Monitor of window '{}' is no longer available, closing it", instance_id);
+                        self.close_window_if_open(&instance_id);
+                        self.instance_id_to_args.insert(instance_id.clone(), window_args);
+                        self.failed_windows.insert(instance_id);
+                        None
+                    }
+                    (Err(_), None) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Move an open window to a new position or change its size, without recreating it.
+    /// Like the geometry given when opening a window, the new geometry is remembered when the window is opened again.
+    fn move_window(
+        &mut self,
+        instance_id: &str,
+        pos: Option<Coords>,
+        size: Option<Coords>,
+        anchor: Option<AnchorPoint>,
+    ) -> Result<()> {
+        let window = self.open_windows.get(instance_id).ok_or_else(|| window_not_open_error("move", instance_id))?;
+        let window_args = self
+            .instance_id_to_args
+            .get_mut(instance_id)
+            .with_context(|| format!("Missing arguments of open window '{}'", instance_id))?;
+        window_args.pos = pos.or(window_args.pos);
+        window_args.size = size.or(window_args.size);
+        window_args.anchor = anchor.or(window_args.anchor);
+        let window_args = window_args.clone();
+
+        let window_def = self.get_window_definition_for(&window_args)?;
+        let geometry = window_def.geometry.with_context(|| format!("Window '{}' has no geometry to change", instance_id))?;
+        let monitor_geometry = get_monitor_geometry(window_def.monitor.clone())?;
+        self.saved_window_geometry.insert(instance_id.to_string(), geometry);
+        window.placement.set(Some((geometry, monitor_geometry)));
+
+        if size.is_some() {
+            let actual_window_rect = with_minimum_window_size(get_window_rectangle(geometry, monitor_geometry));
+            window.gtk_window.set_size_request(actual_window_rect.width(), actual_window_rect.height());
+            window.gtk_window.resize(actual_window_rect.width(), actual_window_rect.height());
+            apply_max_window_size(&window.gtk_window, geometry, monitor_geometry);
+        }
+        B::apply_position(&window.gtk_window, geometry, monitor_geometry);
+        #[cfg(feature = "x11")]
+        apply_window_position(geometry, monitor_geometry, &window.gtk_window)?;
+        Ok(())
+    }
+
+    /// Get all global variables used by open windows, together with the ids of the windows using them, sorted by name.
+    fn active_variables(&self) -> Vec<(VarName, Vec<String>)> {
+        let scope_graph = self.scope_graph.borrow();
+        let mut windows_using_var = HashMap::<VarName, Vec<String>>::new();
+        for window in self.open_windows.values() {
+            for name in scope_graph.variables_used_in_self_or_subscopes_of(window.scope_index) {
+                if scope_graph.global_scope().data.contains_key(&name) {
+                    windows_using_var.entry(name).or_default().push(window.instance_id.clone());
+                }
+            }
+        }
+        windows_using_var
+            .into_iter()
+            .map(|(name, windows)| (name, windows.into_iter().sorted().collect()))
+            .sorted_by(|a, b| a.0 .0.cmp(&b.0 .0))
+            .collect()
+    }
+
+    /// Close a window and do all the required cleanups in the scope_graph and script_var_handler.
+    /// Fails with [`ErrorKind::NoSuchWindow`] if the window is not open, see [`Self::close_window_if_open`].
+    fn close_window(&mut self, instance_id: &str) -> Result<()> {
+        if self.remove_window(instance_id, true) {
+            Ok(())
+        } else {
+            Err(window_not_open_error("close", instance_id))
+        }
+    }
+
+    /// Close a window if it is open, returning whether it was open.
+    /// This is meant for callers that consider a window that is already closed to be closed successfully.
+    fn close_window_if_open(&mut self, instance_id: &str) -> bool {
+        self.remove_window(instance_id, false)
+    }
+
+    /// Close a window if it is open, playing its animation if `animate` is set, and returning whether it was open.
+    fn remove_window(&mut self, instance_id: &str, animate: bool) -> bool {
+        if let Some(cancelled) = self.close_timers.remove(instance_id) {
+            cancelled.set(true);
+        }
+        let eww_window = match self.open_windows.remove(instance_id) {
+            Some(eww_window) => eww_window,
+            None => return false,
+        };
+        self.instance_id_to_args.remove(instance_id);
 
         let scope_index = eww_window.scope_index;
-        eww_window.close();
+        if animate {
+            eww_window.close_animated::<B>();
+        } else {
+            eww_window.close();
+        }
 
         self.scope_graph.borrow_mut().remove_scope(scope_index);
+        self.stop_unused_script_vars();
+
+        true
+    }
+
+    /// Close a window, or if no window with that id is open, all instances of it that were opened with `--all-monitors`.
+    fn close_window_or_monitor_instances(&mut self, instance_id: &str) -> Result<()> {
+        if self.open_windows.contains_key(instance_id) {
+            return self.close_window(instance_id);
+        }
+        let monitor_instances: Vec<String> = self
+            .instance_id_to_args
+            .values()
+            .filter(|args| match &args.monitor {
+                Some(MonitorIdentifier::Numeric(index)) => args.instance_id == format!("{}-{}", instance_id, index),
+                _ => false,
+            })
+            .map(|args| args.instance_id.clone())
+            .filter(|id| self.open_windows.contains_key(id))
+            .collect();
+        if monitor_instances.is_empty() {
+            return self.close_window(instance_id);
+        }
+        for id in monitor_instances {
+            self.close_window(&id)?;
+        }
+        Ok(())
+    }
 
+    /// Stop the script-vars of all global variables that are no longer used by any window.
+    fn stop_unused_script_vars(&self) {
         let unused_variables = self.scope_graph.borrow().currently_unused_globals();
         for unused_var in unused_variables {
             log::debug!("stopping script-var {}", &unused_var);
             self.script_var_handler.stop_for_variable(unused_var.clone());
         }
+    }
 
+    /// Replay recorded actions in a separate thread, sending them to the app one after another
+    /// and waiting for each of them to be handled before sending the next one.
+    fn replay_actions(&self, actions: Vec<RecordedAction>, keep_timing: bool, sender: DaemonResponseSender) -> Result<()> {
+        let app_evt_send = self.app_evt_send.clone();
+        std::thread::Builder::new().name("action-replay".to_string()).spawn(move || {
+            let action_count = actions.len();
+            let mut failures = Vec::new();
+            for (index, RecordedAction { delay_ms, action }) in actions.into_iter().enumerate() {
+                if keep_timing {
+                    std::thread::sleep(Duration::from_millis(delay_ms));
+                }
+                let (command, response_recv) = action.into_daemon_command();
+                if app_evt_send.send(command).is_err() {
+                    return;
+                }
+                if let Some(mut response_recv) = response_recv {
+                    if let Some(DaemonResponse::Failure { message, .. }) = response_recv.blocking_recv() {
+                        failures.push(format!("Action {} failed: {}", index + 1, message));
+                    }
+                }
+            }
+            let result = if failures.is_empty() {
+                sender.send_success(format!("Replayed {} actions", action_count))
+            } else {
+                sender.send_failure(failures.join("\n"))
+            };
+            crate::print_result_err!("responding to replay command", result);
+        })?;
         Ok(())
     }
 
-    fn open_window(
+    /// Open a window, closing it instead if it is already open and `should_toggle` is set.
+    /// Otherwise, an already open window is handled as specified by `if_open`.
+    fn open_or_toggle_window(
         &mut self,
-        window_name: &str,
-        pos: Option<Coords>,
-        size: Option<Coords>,
-        monitor: Option<MonitorIdentifier>,
-        anchor: Option<AnchorPoint>,
+        window_args: &WindowArguments,
+        should_toggle: bool,
+        if_open: AlreadyOpenBehavior,
     ) -> Result<()> {
-        self.failed_windows.remove(window_name);
-        log::info!("Opening window {}", window_name);
+        let instance_id = window_args.instance_id.as_str();
+        if !self.open_windows.contains_key(instance_id) {
+            return self.open_window(window_args);
+        } else if should_toggle {
+            return self.close_window(instance_id);
+        }
+        match if_open {
+            AlreadyOpenBehavior::Reopen => self.open_window(window_args),
+            AlreadyOpenBehavior::Ignore => Ok(()),
+            AlreadyOpenBehavior::Raise => {
+                if let Some(window) = self.open_windows.get(instance_id) {
+                    window.gtk_window.present();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn open_window(&mut self, window_args: &WindowArguments) -> Result<()> {
+        let instance_id = window_args.instance_id.as_str();
+        self.failed_windows.remove(instance_id);
+        log::info!("Opening window {} as '{}'", window_args.window_name, instance_id);
 
         // if an instance of this is already running, close it
-        if self.open_windows.contains_key(window_name) {
-            self.close_window(window_name)?;
-        }
+        self.close_window_if_open(instance_id);
+        self.instance_id_to_args.insert(instance_id.to_string(), window_args.clone());
 
         let open_result: Result<_> = try {
-            let mut window_def = self.eww_config.get_window(window_name)?.clone();
+            let window_name = window_args.window_name.as_str();
+            let window_def = self.get_window_definition_for(window_args)?;
             assert_eq!(window_def.name, window_name, "window definition name did not equal the called window");
-            window_def.geometry = window_def.geometry.map(|x| x.override_if_given(anchor, pos, size));
+            // Geometry overrides given when opening a window are remembered, and reused when the window is opened again.
+            let has_overrides = window_args.anchor.is_some() || window_args.pos.is_some() || window_args.size.is_some();
+            if window_args.remember_geometry && has_overrides {
+                if let Some(geometry) = window_def.geometry {
+                    self.saved_window_geometry.insert(instance_id.to_string(), geometry);
+                }
+            }
+
+            let monitor_geometry = get_monitor_geometry(window_def.monitor.clone())?;
+
+            let mut window_variables = window_args.get_local_window_variables(&window_def)?;
+            window_variables.extend(config::inbuilt::get_window_magic_vars(monitor_geometry));
 
             let root_index = self.scope_graph.borrow().root_index;
 
             let window_scope = self.scope_graph.borrow_mut().register_new_scope(
-                window_name.to_string(),
+                instance_id.to_string(),
                 Some(root_index),
                 root_index,
-                HashMap::new(),
+                window_variables,
             )?;
 
             let root_widget = crate::widgets::build_widget::build_gtk_widget(
@@ -351,9 +1487,7 @@ impl<B: DisplayBackend> App<B> {
                 None,
             )?;
 
-            let monitor_geometry = get_monitor_geometry(monitor.or_else(|| window_def.monitor.clone()))?;
-
-            let mut eww_window = initialize_window::<B>(monitor_geometry, root_widget, window_def, window_scope)?;
+            let mut eww_window = initialize_window::<B>(window_args, monitor_geometry, root_widget, window_def, window_scope)?;
             eww_window.gtk_window.style_context().add_class(window_name);
 
             // initialize script var handlers for variables. As starting a scriptvar with the script_var_handler is idempodent,
@@ -367,75 +1501,389 @@ impl<B: DisplayBackend> App<B> {
 
             eww_window.destroy_event_handler_id = Some(eww_window.gtk_window.connect_destroy({
                 let app_evt_sender = self.app_evt_send.clone();
-                let window_name: String = eww_window.name.to_string();
+                let instance_id = eww_window.instance_id.to_string();
                 move |_| {
                     // we don't care about the actual error response from the daemon as this is mostly just a fallback.
                     // Generally, this should get disconnected before the gtk window gets destroyed.
                     // It serves as a fallback for when the window is closed manually.
                     let (response_sender, _) = daemon_response::create_pair();
-                    let command = DaemonCommand::CloseWindows { windows: vec![window_name.clone()], sender: response_sender };
+                    let command = DaemonCommand::CloseWindows { windows: vec![instance_id.clone()], sender: response_sender };
                     if let Err(err) = app_evt_sender.send(command) {
                         log::error!("Error sending close window command to daemon after gtk window destroy event: {}", err);
                     }
                 }
             }));
 
-            self.open_windows.insert(window_name.to_string(), eww_window);
+            if let Some(animation) = eww_window.animation {
+                slide_window::<B>(&eww_window.gtk_window, eww_window.placement.clone(), animation, true, |_| {});
+            }
+            self.open_windows.insert(instance_id.to_string(), eww_window);
+
+            if let Some(close_after) = window_args.close_after {
+                let cancelled = Rc::new(Cell::new(false));
+                if let Some(previous) = self.close_timers.insert(instance_id.to_string(), cancelled.clone()) {
+                    previous.set(true);
+                }
+                let app_evt_sender = self.app_evt_send.clone();
+                let instance_id = instance_id.to_string();
+                glib::timeout_add_local_once(close_after, move || {
+                    if cancelled.get() {
+                        return;
+                    }
+                    let (response_sender, _) = daemon_response::create_pair();
+                    let command = DaemonCommand::CloseWindows { windows: vec![instance_id], sender: response_sender };
+                    if let Err(err) = app_evt_sender.send(command) {
+                        log::error!("Error sending close window command to daemon after timeout: {}", err);
+                    }
+                });
+            }
         };
 
         if let Err(err) = open_result {
-            self.failed_windows.insert(window_name.to_string());
-            Err(err).with_context(|| format!("failed to open window `{}`", window_name))
+            self.failed_windows.insert(instance_id.to_string());
+            Err(err).with_context(|| format!("failed to open window `{}`", instance_id))
         } else {
             Ok(())
         }
     }
 
-    /// Load the given configuration, reloading all script-vars and attempting to reopen all windows that where opened.
+    /// Load the given configuration, restarting changed script-vars and attempting to reopen all windows that where opened.
     pub fn load_config(&mut self, config: config::EwwConfig) -> Result<()> {
+        // If only window definitions changed, the current state and script-vars stay valid,
+        // so only the windows whose definition changed need to be reopened.
+        if self.eww_config.has_same_state_as(&config) {
+            log::info!("Reloading changed windows");
+            log::trace!("loading config: {:#?}", config);
+            let old_config = std::mem::replace(&mut self.eww_config, config);
+            let instances: Vec<WindowArguments> = self
+                .open_windows
+                .keys()
+                .chain(self.failed_windows.iter())
+                .dedup()
+                .filter_map(|instance_id| self.instance_id_to_args.get(instance_id))
+                .filter(|args| {
+                    self.failed_windows.contains(&args.instance_id)
+                        || old_config.get_window(&args.window_name).ok() != self.eww_config.get_window(&args.window_name).ok()
+                })
+                .cloned()
+                .collect();
+            for window_args in &instances {
+                self.open_window(window_args)?;
+            }
+            return Ok(());
+        }
+
         log::info!("Reloading windows");
+        log::trace!("loading config: {:#?}", config);
 
-        self.script_var_handler.stop_all();
-        let old_handler = std::mem::replace(&mut self.script_var_handler, script_var_handler::init(self.app_evt_send.clone()));
-        old_handler.join_thread();
+        // Only restart the script-vars whose definition changed, and keep the values of all others.
+        self.script_var_handler.reconcile(config.get_script_vars().clone());
+        let unchanged_script_var_values: HashMap<VarName, DynVal> = self
+            .scope_graph
+            .borrow()
+            .global_scope()
+            .data
+            .iter()
+            .filter(|(name, _)| {
+                let old_definition = self.eww_config.get_script_var(name).ok();
+                old_definition.is_some() && old_definition == config.get_script_var(name).ok()
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
 
-        log::trace!("loading config: {:#?}", config);
+        let instances: Vec<WindowArguments> = self
+            .open_windows
+            .keys()
+            .chain(self.failed_windows.iter())
+            .dedup()
+            .filter_map(|instance_id| self.instance_id_to_args.get(instance_id).cloned())
+            .collect();
+
+        // The windows are closed without stopping the script-vars they use, as they are reopened right away.
+        // Until then, they count as failed, such that they are retried on the next reload if reopening fails.
+        for (instance_id, eww_window) in self.open_windows.drain() {
+            eww_window.close();
+            // The timer of the closed window must not close the window reopened in its place.
+            if let Some(cancelled) = self.close_timers.remove(&instance_id) {
+                cancelled.set(true);
+            }
+            self.failed_windows.insert(instance_id);
+        }
 
         self.eww_config = config;
-        self.scope_graph.borrow_mut().clear(self.eww_config.generate_initial_state()?);
+        let mut kept_values = persisted_vars::read_for_config(self.paths.get_persisted_vars_file(), &self.eww_config);
+        kept_values.extend(unchanged_script_var_values);
+        self.scope_graph.borrow_mut().clear(self.eww_config.generate_state_keeping(kept_values)?);
+
+        let open_result: Result<()> = try {
+            for window_args in &instances {
+                self.open_window(window_args)?;
+            }
+        };
+        self.stop_unused_script_vars();
+        open_result
+    }
 
-        let window_names: Vec<String> =
-            self.open_windows.keys().cloned().chain(self.failed_windows.iter().cloned()).dedup().collect();
-        for window_name in &window_names {
-            self.open_window(window_name, None, None, None, None)?;
+    /// Switch to the configuration in another directory, closing all windows of the current configuration,
+    /// and then opening the given windows of the new one.
+    /// If the new configuration can't be read, the current one stays active.
+    fn switch_config(&mut self, config_dir: &std::path::Path, windows: &[String]) -> Result<()> {
+        let paths = self.paths.clone().with_config_dir(config_dir)?;
+        let config = config::read_from_eww_paths(&paths)?;
+        log::info!("Switching to the configuration in {}", paths.get_config_dir().display());
+
+        for instance_id in self.open_windows.keys().cloned().collect::<Vec<String>>() {
+            self.remove_window(&instance_id, true);
+        }
+        self.failed_windows.clear();
+        self.instance_id_to_args.clear();
+        self.saved_window_geometry.clear();
+
+        self.paths = paths;
+        self.config_dir_send.send_replace(self.paths.config_dir.clone());
+        self.load_config(config)?;
+        self.reload_css()?;
+
+        let mut errors = windows
+            .iter()
+            .map(|window_name| self.open_window(&WindowArguments::new_from_name(window_name.clone())))
+            .filter_map(Result::err)
+            .collect::<Vec<_>>();
+        errors.extend(self.open_startup_windows());
+        let errors = errors.iter().map(error_handling_ctx::format_error).collect::<Vec<_>>();
+        if !errors.is_empty() {
+            return Err(anyhow!("Failed to open windows of the new configuration:\n{}", errors.join("\n")));
         }
         Ok(())
     }
 
-    /// Load a given CSS string into the gtk css provider, returning a nicely formatted [`DiagError`] when GTK errors out
+    /// Open all windows marked with `:open-on-startup`, unless an instance of them is already open.
+    pub fn open_startup_windows(&mut self) -> Vec<anyhow::Error> {
+        let startup_windows = self
+            .eww_config
+            .get_windows()
+            .values()
+            .filter(|window_def| window_def.open_on_startup)
+            .map(|window_def| window_def.name.clone())
+            .sorted()
+            .collect::<Vec<_>>();
+        startup_windows
+            .into_iter()
+            .filter(|window_name| !self.open_windows.values().any(|window| &window.name == window_name))
+            .map(|window_name| self.open_window(&WindowArguments::new_from_name(window_name)))
+            .filter_map(Result::err)
+            .collect()
+    }
+
+    /// Read the stylesheet from the configuration directory and load it into the gtk css provider.
+    fn reload_css(&mut self) -> Result<()> {
+        let (file_id, css) = crate::config::scss::parse_scss_from_config(self.paths.get_config_dir())?;
+        self.load_css(file_id, &css)
+    }
+
+    /// Load a given CSS string into the gtk css provider, returning a nicely formatted [`DiagError`] when GTK errors out.
+    /// If only the `@define-color` definitions changed since the last time, only those get updated,
+    /// which is a lot cheaper than reloading the whole stylesheet.
     pub fn load_css(&mut self, file_id: usize, css: &str) -> Result<()> {
-        if let Err(err) = self.css_provider.load_from_data(css.as_bytes()) {
-            static PATTERN: Lazy<regex::Regex> = Lazy::new(|| regex::Regex::new(r"[^:]*:(\d+):(\d+)(.*)$").unwrap());
-            let nice_error_option: Option<_> = try {
-                let captures = PATTERN.captures(err.message())?;
-                let line = captures.get(1).unwrap().as_str().parse::<usize>().ok()?;
-                let msg = captures.get(3).unwrap().as_str();
-                let db = error_handling_ctx::FILE_DATABASE.read().ok()?;
-                let line_range = db.line_range(file_id, line - 1).ok()?;
-                let span = Span(line_range.start, line_range.end - 1, file_id);
-                DiagError(gen_diagnostic!(msg, span))
-            };
-            match nice_error_option {
-                Some(error) => Err(anyhow!(error)),
-                None => Err(anyhow!("CSS error: {}", err.message())),
+        let stylesheet = SplitStylesheet::from_css(css);
+        if let Some(loaded_stylesheet) = &self.loaded_stylesheet {
+            if loaded_stylesheet.rest == stylesheet.rest {
+                if loaded_stylesheet.color_definitions != stylesheet.color_definitions {
+                    log::debug!("Only color definitions changed, updating them without reloading the stylesheet");
+                    self.color_css_provider
+                        .load_from_data(stylesheet.color_definitions_css().as_bytes())
+                        .map_err(|err| anyhow!("CSS error: {}", err.message()))?;
+                }
+                self.loaded_stylesheet = Some(stylesheet);
+                return Ok(());
+            }
+        }
+
+        let mut errors = load_css_collecting_errors(&self.css_provider, file_id, css);
+        match errors.len() {
+            0 => {
+                // The full stylesheet contains the current color definitions, so the overrides are no longer needed.
+                self.color_css_provider.load_from_data(b"").map_err(|err| anyhow!("CSS error: {}", err.message()))?;
+                self.loaded_stylesheet = Some(stylesheet);
+                Ok(())
+            }
+            1 => Err(errors.remove(0)),
+            _ => Err(anyhow!("{}", errors.iter().map(error_handling_ctx::format_error).join("\n"))),
+        }
+    }
+}
+
+/// Merge consecutive [`DaemonCommand::UpdateVars`] commands into one, which only contains the latest value of each variable.
+/// Other commands are kept in their original order, such that they still observe the same state as before.
+pub fn coalesce_var_updates(commands: impl IntoIterator<Item = DaemonCommand>) -> Vec<DaemonCommand> {
+    let mut coalesced = Vec::new();
+    for command in commands {
+        if let DaemonCommand::UpdateVars(new_mappings) = command {
+            if let Some(DaemonCommand::UpdateVars(mappings)) = coalesced.last_mut() {
+                mappings.extend(new_mappings);
+            } else {
+                coalesced.push(DaemonCommand::UpdateVars(new_mappings));
             }
         } else {
-            Ok(())
+            coalesced.push(command);
+        }
+    }
+    for command in coalesced.iter_mut() {
+        if let DaemonCommand::UpdateVars(mappings) = command {
+            let mut seen = HashSet::new();
+            let mut latest =
+                std::mem::take(mappings).into_iter().rev().filter(|(name, _)| seen.insert(name.clone())).collect_vec();
+            latest.reverse();
+            *mappings = latest;
+        }
+    }
+    coalesced
+}
+
+/// Evaluate the condition of a [`VarWaiter`], and respond to the client if the condition holds or fails to evaluate.
+/// Returns true if a response was sent, marking the waiter as responded to.
+fn try_respond_to_var_waiter(scope_graph: &ScopeGraph, waiter: &VarWaiter) -> bool {
+    let result: Result<bool> =
+        try { scope_graph.evaluate_simplexpr_in_scope(scope_graph.root_index, &waiter.predicate)?.as_bool()? };
+    let response = match result {
+        Ok(false) => return false,
+        Ok(true) => {
+            let value = scope_graph.global_scope().data.get(&waiter.name).map(|x| x.to_string()).unwrap_or_default();
+            waiter.sender.send_success(value)
         }
+        Err(err) => waiter.sender.respond_with_result::<()>(Err(err)),
+    };
+    waiter.responded.set(true);
+    crate::print_result_err!("responding to a client waiting for a variable", &response);
+    true
+}
+
+/// Parse a simplexpr expression that was passed in via the CLI.
+/// The code is registered in the file database so that errors can be displayed nicely.
+fn parse_simplexpr_from_cli(name: &str, code: &str) -> Result<SimplExpr> {
+    let file_id = error_handling_ctx::FILE_DATABASE.write().unwrap().insert_string(name.to_string(), code.to_string())?;
+    simplexpr::parse_string(0, file_id, code).map_err(|err| anyhow!(DiagError(lalrpop_error_to_diagnostic(&err.source, file_id))))
+}
+
+/// Error for trying to perform an action on a window instance that is not open.
+fn window_not_open_error(action: &str, instance_id: &str) -> anyhow::Error {
+    ErrorKind::NoSuchWindow.error(format!("Tried to {} window named '{}', but no such window was open", action, instance_id))
+}
+
+/// Load CSS into a provider, returning every error in it as a nicely formatted [`DiagError`] where possible.
+pub fn load_css_collecting_errors(provider: &gtk::CssProvider, file_id: usize, css: &str) -> Vec<anyhow::Error> {
+    // GTK only returns the first error it encounters, so all errors are collected from the parsing-error signal instead.
+    let parsing_errors = Rc::new(RefCell::new(Vec::new()));
+    let handler_id = provider.connect_parsing_error({
+        let parsing_errors = parsing_errors.clone();
+        move |_, section, err| {
+            if err.matches(gtk::CssProviderError::Deprecated) {
+                log::warn!("CSS line {}: {}", section.start_line() + 1, err.message());
+            } else {
+                parsing_errors.borrow_mut().push((section.start_line() as usize, err.message().to_string()));
+            }
+        }
+    });
+    let load_result = provider.load_from_data(css.as_bytes());
+    provider.disconnect(handler_id);
+
+    let mut errors = parsing_errors.take().into_iter().map(|(line, msg)| css_error_at_line(file_id, line, &msg)).collect_vec();
+    if let (Err(err), true) = (&load_result, errors.is_empty()) {
+        static PATTERN: Lazy<regex::Regex> = Lazy::new(|| regex::Regex::new(r"[^:]*:(\d+):(\d+)(.*)$").unwrap());
+        let error = PATTERN.captures(err.message()).and_then(|captures| {
+            let line = captures.get(1).unwrap().as_str().parse::<usize>().ok()?;
+            Some(css_error_at_line(file_id, line.checked_sub(1)?, captures.get(3).unwrap().as_str()))
+        });
+        errors.push(error.unwrap_or_else(|| anyhow!("CSS error: {}", err.message())));
+    }
+    errors
+}
+
+/// Create an error pointing at a (zero-based) line of the stylesheet, falling back to a plain error if the line can't be found.
+fn css_error_at_line(file_id: usize, line: usize, msg: &str) -> anyhow::Error {
+    let span: Option<Span> = try {
+        let db = error_handling_ctx::FILE_DATABASE.read().ok()?;
+        let line_range = db.line_range(file_id, line).ok()?;
+        Span(line_range.start, line_range.end.saturating_sub(1), file_id)
+    };
+    match span {
+        Some(span) => anyhow!(DiagError(gen_diagnostic!(msg, span))),
+        None => anyhow!("CSS error in line {}: {}", line + 1, msg),
+    }
+}
+
+/// Format a widget and all of its children as an indented tree, listing their type, CSS classes and allocated size.
+fn format_widget_tree(widget: &gtk::Widget, depth: usize, output: &mut String) {
+    let allocation = widget.allocation();
+    let classes = widget.style_context().list_classes();
+    output.push_str(&format!("{}{}", "  ".repeat(depth), widget.type_().name()));
+    if !classes.is_empty() {
+        output.push_str(&format!(" .{}", classes.iter().join(".")));
+    }
+    output.push_str(&format!(" {}x{}\n", allocation.width(), allocation.height()));
+    if let Some(container) = widget.downcast_ref::<gtk::Container>() {
+        for child in container.children() {
+            format_widget_tree(&child, depth + 1, output);
+        }
+    }
+}
+
+/// Get the name of the variant of a [`DaemonCommand`], used to describe the cause of errors.
+pub fn get_command_name(command: &DaemonCommand) -> &'static str {
+    match command {
+        DaemonCommand::NoOp => "NoOp",
+        DaemonCommand::Ping(..) => "Ping",
+        DaemonCommand::UpdateVars(..) => "UpdateVars",
+        DaemonCommand::ReloadConfigAndCss(..) => "ReloadConfigAndCss",
+        DaemonCommand::ReloadCss(..) => "ReloadCss",
+        DaemonCommand::ReloadConfig(..) => "ReloadConfig",
+        DaemonCommand::OpenInspector => "OpenInspector",
+        DaemonCommand::OpenMany { .. } => "OpenMany",
+        DaemonCommand::OpenWindow { .. } => "OpenWindow",
+        DaemonCommand::GetWindowGeometry { .. } => "GetWindowGeometry",
+        DaemonCommand::MoveWindow { .. } => "MoveWindow",
+        DaemonCommand::SetWindowClass { .. } => "SetWindowClass",
+        DaemonCommand::CloseWindows { .. } => "CloseWindows",
+        DaemonCommand::SwitchConfig { .. } => "SwitchConfig",
+        DaemonCommand::FocusWindow { .. } => "FocusWindow",
+        DaemonCommand::ReassertGeometry(..) => "ReassertGeometry",
+        DaemonCommand::ForgetGeometry { .. } => "ForgetGeometry",
+        DaemonCommand::MonitorsChanged => "MonitorsChanged",
+        DaemonCommand::Redraw { .. } => "Redraw",
+        DaemonCommand::KillServer => "KillServer",
+        DaemonCommand::CloseAll => "CloseAll",
+        DaemonCommand::PauseScriptVars => "PauseScriptVars",
+        DaemonCommand::ResumeScriptVars => "ResumeScriptVars",
+        DaemonCommand::RestartScriptVar { .. } => "RestartScriptVar",
+        DaemonCommand::SetVar { .. } => "SetVar",
+        DaemonCommand::PrintState { .. } => "PrintState",
+        DaemonCommand::GetVar { .. } => "GetVar",
+        DaemonCommand::GetWidgetAttribute { .. } => "GetWidgetAttribute",
+        DaemonCommand::InspectWindow { .. } => "InspectWindow",
+        DaemonCommand::Screenshot { .. } => "Screenshot",
+        DaemonCommand::GetWindowMonitor { .. } => "GetWindowMonitor",
+        DaemonCommand::Subscribe { .. } => "Subscribe",
+        DaemonCommand::WaitForVar { .. } => "WaitForVar",
+        DaemonCommand::ListWindowsWithGeometry { .. } => "ListWindowsWithGeometry",
+        DaemonCommand::IsWindowOpen { .. } => "IsWindowOpen",
+        DaemonCommand::PrintActiveVars { .. } => "PrintActiveVars",
+        DaemonCommand::PrintDebug(..) => "PrintDebug",
+        DaemonCommand::DumpConfig(..) => "DumpConfig",
+        DaemonCommand::PrintGraph(..) => "PrintGraph",
+        DaemonCommand::PrintWindows { .. } => "PrintWindows",
+        DaemonCommand::PrintBuiltinWidgets(..) => "PrintBuiltinWidgets",
+        DaemonCommand::PrintWidgets { .. } => "PrintWidgets",
+        DaemonCommand::PrintProcessOutput { .. } => "PrintProcessOutput",
+        DaemonCommand::PrintErrors { .. } => "PrintErrors",
+        DaemonCommand::DescribeRuntime(..) => "DescribeRuntime",
+        DaemonCommand::StartRecording { .. } => "StartRecording",
+        DaemonCommand::StopRecording(..) => "StopRecording",
+        DaemonCommand::Replay { .. } => "Replay",
     }
 }
 
 fn initialize_window<B: DisplayBackend>(
+    window_args: &WindowArguments,
     monitor_geometry: gdk::Rectangle,
     root_widget: gtk::Widget,
     window_def: WindowDefinition,
@@ -444,14 +1892,17 @@ fn initialize_window<B: DisplayBackend>(
     let window = B::initialize_window(&window_def, monitor_geometry)
         .with_context(|| format!("monitor {} is unavailable", window_def.monitor.clone().unwrap()))?;
 
+    // Compositors use the namespace in their rules, so default to something that identifies the window.
+    B::set_namespace(&window, window_def.backend_options.wayland.namespace.as_deref().unwrap_or(&window_def.name));
     window.set_title(&format!("Eww - {}", window_def.name));
     window.set_position(gtk::WindowPosition::None);
     window.set_gravity(gdk::Gravity::Center);
 
     if let Some(geometry) = window_def.geometry {
-        let actual_window_rect = get_window_rectangle(geometry, monitor_geometry);
+        let actual_window_rect = with_minimum_window_size(get_window_rectangle(geometry, monitor_geometry));
         window.set_size_request(actual_window_rect.width(), actual_window_rect.height());
         window.set_default_size(actual_window_rect.width(), actual_window_rect.height());
+        apply_max_window_size(&window, geometry, monitor_geometry);
     }
     window.set_decorated(false);
     window.set_skip_taskbar_hint(true);
@@ -463,25 +1914,133 @@ fn initialize_window<B: DisplayBackend>(
 
     window.add(&root_widget);
 
+    let (_, natural_size) = root_widget.preferred_size();
+    if natural_size.width() == 0 || natural_size.height() == 0 {
+        log::warn!(
+            "The content of window {} has a size of {}x{}, so the window may not be visible",
+            window_def.name,
+            natural_size.width(),
+            natural_size.height()
+        );
+    }
+
     window.realize();
 
+    if let Some(cursor) = &window_def.cursor {
+        apply_window_cursor(&window, &window_def.name, cursor);
+    }
+    if window_def.pass_through {
+        B::set_input_passthrough(&window, true);
+    }
+
+    if let Some(opacity) = window_def.opacity {
+        window.set_opacity(opacity);
+        // Without a compositor, the window stays fully opaque.
+        if !window.screen().map_or(false, |screen| screen.is_composited()) {
+            log::warn!("Window {} has an opacity set, which requires a running compositor", window_def.name);
+        }
+    }
+
+    let placement = Rc::new(Cell::new(window_def.geometry.map(|geometry| (geometry, monitor_geometry))));
+
     #[cfg(feature = "x11")]
     {
         if let Some(geometry) = window_def.geometry {
             let _ = apply_window_position(geometry, monitor_geometry, &window);
             if window_def.backend_options.x11.window_type != yuck::config::backend_window_options::X11WindowType::Normal {
+                let placement = placement.clone();
                 window.connect_configure_event(move |window, _| {
-                    let _ = apply_window_position(geometry, monitor_geometry, window);
+                    if let Some((geometry, monitor_geometry)) = placement.get() {
+                        let _ = apply_window_position(geometry, monitor_geometry, window);
+                    }
                     false
                 });
+            } else {
+                // Normal windows may be moved around freely, so they are only placed again when their content changes size,
+                // to keep them at their anchor point.
+                let placement = placement.clone();
+                let last_size = Cell::new(None);
+                window.connect_size_allocate(move |window, allocation| {
+                    let size = (allocation.width(), allocation.height());
+                    let size_changed = last_size.replace(Some(size)).map_or(false, |last_size| last_size != size);
+                    if let Some((geometry, monitor_geometry)) = placement.get().filter(|_| size_changed) {
+                        let _ = apply_window_position(geometry, monitor_geometry, window);
+                    }
+                });
             }
         }
-        display_backend::set_xprops(&window, monitor_geometry, &window_def)?;
+    }
+
+    if let Err(err) = B::set_window_properties(&window, monitor_geometry, &window_def) {
+        // The window has already been realized, so it needs to be closed explicitly to not leak it.
+        window.close();
+        return Err(err);
     }
 
     window.show_all();
 
-    Ok(EwwWindow { name: window_def.name, gtk_window: window, scope_index: window_scope, destroy_event_handler_id: None })
+    Ok(EwwWindow {
+        name: window_def.name,
+        instance_id: window_args.instance_id.clone(),
+        gtk_window: window,
+        root_widget,
+        scope_index: window_scope,
+        destroy_event_handler_id: None,
+        placement,
+        monitor_geometry,
+        runtime_classes: Vec::new(),
+        animation: window_def.animation,
+    })
+}
+
+/// Apply the size, position, stacking and window manager hints of a window definition to an already initialized window.
+fn apply_window_placement(window: &gtk::Window, window_def: &WindowDefinition, monitor_geometry: gdk::Rectangle) -> Result<()> {
+    if let Some(geometry) = window_def.geometry {
+        let actual_window_rect = with_minimum_window_size(get_window_rectangle(geometry, monitor_geometry));
+        window.set_size_request(actual_window_rect.width(), actual_window_rect.height());
+        window.resize(actual_window_rect.width(), actual_window_rect.height());
+        apply_max_window_size(window, geometry, monitor_geometry);
+        #[cfg(feature = "x11")]
+        apply_window_position(geometry, monitor_geometry, window)?;
+    }
+
+    #[cfg(feature = "x11")]
+    {
+        window.set_keep_above(window_def.stacking.is_above());
+        window.set_keep_below(window_def.stacking.is_below());
+        display_backend::set_xprops(window, monitor_geometry, window_def)?;
+    }
+    Ok(())
+}
+
+/// Show the named cursor while hovering the window, keeping the default cursor if there is no cursor with that name.
+fn apply_window_cursor(window: &gtk::Window, window_name: &str, cursor_name: &str) {
+    let gdk_window = match window.window() {
+        Some(gdk_window) => gdk_window,
+        None => return,
+    };
+    match gdk::Cursor::from_name(&gdk_window.display(), cursor_name) {
+        Some(cursor) => gdk_window.set_cursor(Some(&cursor)),
+        None => log::warn!("Unknown cursor `{}` in window {}, using the default cursor instead", cursor_name, window_name),
+    }
+}
+
+/// Grow a window rectangle to a size of at least 1x1, as some backends reject windows with a size of zero.
+fn with_minimum_window_size(rect: gdk::Rectangle) -> gdk::Rectangle {
+    gdk::Rectangle::new(rect.x(), rect.y(), rect.width().max(1), rect.height().max(1))
+}
+
+/// Limit the size of a window to the `max-width` and `max-height` of its geometry.
+/// Unlike the size request, this also applies when the window is sized to fit its content.
+fn apply_max_window_size(window: &gtk::Window, geometry: WindowGeometry, monitor_geometry: gdk::Rectangle) {
+    if geometry.max_width.is_none() && geometry.max_height.is_none() {
+        return;
+    }
+    let max_width = geometry.max_width.map_or(monitor_geometry.width(), |max| max.pixels_relative_to(monitor_geometry.width()));
+    let max_height =
+        geometry.max_height.map_or(monitor_geometry.height(), |max| max.pixels_relative_to(monitor_geometry.height()));
+    let hints = gdk::Geometry::new(0, 0, max_width, max_height, 0, 0, 0, 0, 0.0, 0.0, gdk::Gravity::Center);
+    window.set_geometry_hints(None::<&gtk::Widget>, Some(&hints), gdk::WindowHints::MAX_SIZE);
 }
 
 /// Apply the provided window-positioning rules to the window.
@@ -504,6 +2063,73 @@ fn apply_window_position(
     Ok(())
 }
 
+/// Interval between the frames of window animations
+const ANIMATION_FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Slide a window in from outside of the edge of its monitor given by the animation, or back out of it,
+/// and call `on_done` once the animation is finished.
+/// Windows that haven't been placed yet don't get animated.
+fn slide_window<B: DisplayBackend>(
+    window: &gtk::Window,
+    placement: Rc<Cell<Option<(WindowGeometry, gdk::Rectangle)>>>,
+    animation: SlideAnimation,
+    slide_in: bool,
+    on_done: impl FnOnce(&gtk::Window) + 'static,
+) {
+    let (geometry, monitor_geometry) = match placement.get() {
+        Some(current_placement) => current_placement,
+        None => return on_done(window),
+    };
+    let window_geometry = WindowGeometry { size: Coords::from_pixels(window.size()), ..geometry };
+    let window_rect = get_window_rectangle(window_geometry, monitor_geometry);
+    let (offset_x, offset_y) = geometry.offset.relative_to(monitor_geometry.width(), monitor_geometry.height());
+
+    let apply_frame = {
+        let window = window.clone();
+        let placement = placement.clone();
+        move |progress: f64| {
+            let (delta_x, delta_y) = slide_offset(animation.from, window_rect, monitor_geometry, progress);
+            let frame_offset = Coords::from_pixels((offset_x + delta_x, offset_y + delta_y));
+            let frame_geometry = WindowGeometry { offset: frame_offset, ..geometry };
+            placement.set(Some((frame_geometry, monitor_geometry)));
+            B::apply_position(&window, frame_geometry, monitor_geometry);
+            #[cfg(feature = "x11")]
+            let _ = apply_window_position(frame_geometry, monitor_geometry, &window);
+        }
+    };
+    apply_frame(if slide_in { 0.0 } else { 1.0 });
+
+    let window = window.clone();
+    let start = std::time::Instant::now();
+    let mut on_done = Some(on_done);
+    glib::timeout_add_local(ANIMATION_FRAME_INTERVAL, move || {
+        let progress = (start.elapsed().as_secs_f64() / animation.duration.as_secs_f64()).min(1.0);
+        apply_frame(if slide_in { progress } else { 1.0 - progress });
+        if progress < 1.0 {
+            return glib::Continue(true);
+        }
+        // Keep the original, possibly relative, offset for any later repositioning
+        placement.set(Some((geometry, monitor_geometry)));
+        if let Some(on_done) = on_done.take() {
+            on_done(&window);
+        }
+        glib::Continue(false)
+    });
+}
+
+/// The distance a window is moved from its final position while sliding in from the given edge of its monitor.
+/// At a progress of 0 the window is entirely outside of the monitor, at 1 it has arrived, easing out towards the end.
+fn slide_offset(from: Side, window_rect: gdk::Rectangle, monitor_geometry: gdk::Rectangle, progress: f64) -> (i32, i32) {
+    let remaining = (1.0 - progress.clamp(0.0, 1.0)).powi(3);
+    let (distance_x, distance_y) = match from {
+        Side::Top => (0, monitor_geometry.y() - window_rect.y() - window_rect.height()),
+        Side::Bottom => (0, monitor_geometry.y() + monitor_geometry.height() - window_rect.y()),
+        Side::Left => (monitor_geometry.x() - window_rect.x() - window_rect.width(), 0),
+        Side::Right => (monitor_geometry.x() + monitor_geometry.width() - window_rect.x(), 0),
+    };
+    ((distance_x as f64 * remaining).round() as i32, (distance_y as f64 * remaining).round() as i32)
+}
+
 fn on_screen_changed(window: &gtk::Window, _old_screen: Option<&gdk::Screen>) {
     let visual = window
         .screen()
@@ -511,72 +2137,263 @@ fn on_screen_changed(window: &gtk::Window, _old_screen: Option<&gdk::Screen>) {
     window.set_visual(visual.as_ref());
 }
 
-/// Get the monitor geometry of a given monitor, or the default if none is given
+/// Environment variable that selects the monitor used for windows that don't specify one.
+const DEFAULT_MONITOR_ENV_VAR: &str = "EWW_DEFAULT_MONITOR";
+
+/// Get the monitor geometry of a given monitor, or the default if none is given.
+/// The default monitor is the one set in [`DEFAULT_MONITOR_ENV_VAR`], or the primary monitor otherwise,
+/// falling back to the first monitor if none is marked as primary.
 fn get_monitor_geometry(identifier: Option<MonitorIdentifier>) -> Result<gdk::Rectangle> {
     let display = gdk::Display::default().expect("could not get default display");
-    let monitor = match identifier {
-        Some(ident) => {
-            let mon = get_monitor_from_display(&display, &ident);
-
-            #[cfg(feature = "x11")]
-            {
-                mon.with_context(|| {
-                    let head = format!("Failed to get monitor {}\nThe available monitors are:", ident);
-                    let mut body = String::new();
-                    for m in 0..display.n_monitors() {
-                        if let Some(model) = display.monitor(m).and_then(|x| x.model()) {
-                            body.push_str(format!("\n\t[{}] {}", m, model).as_str());
-                        }
-                    }
-                    format!("{}{}", head, body)
-                })?
-            }
-
-            #[cfg(not(feature = "x11"))]
-            {
-                mon.with_context(|| {
-                    if ident.is_numeric() {
-                        format!("Failed to get monitor {}", ident)
-                    } else {
-                        format!("Using ouput names (\"{}\" in the configuration) is not supported outside of x11 yet", ident)
-                    }
-                })?
+    let monitor = match identifier.or_else(get_default_monitor_from_env) {
+        Some(ident) => get_monitor_from_display(&display, &ident).with_context(|| {
+            let head = format!("Failed to get monitor {}\nThe available monitors are:", ident);
+            let mut body = String::new();
+            for m in 0..display.n_monitors() {
+                if let Some(model) = display.monitor(m).and_then(|x| x.model()) {
+                    body.push_str(format!("\n\t[{}] {}", m, model).as_str());
+                }
             }
-        }
-        None => display
-            .primary_monitor()
-            .context("Failed to get primary monitor from GTK. Try explicitly specifying the monitor on your window.")?,
+            format!("{}{}", head, body)
+        })?,
+        // Not every setup marks a monitor as primary, i.e. on wayland, so fall back to the first monitor in that case.
+        None => display.primary_monitor().or_else(|| display.monitor(0)).with_context(|| {
+            format!(
+                "Failed to get primary monitor from GTK. Try explicitly specifying the monitor on your window, or set {}.",
+                DEFAULT_MONITOR_ENV_VAR
+            )
+        })?,
     };
     Ok(monitor.geometry())
 }
 
+/// Get the arguments for one instance of a window on every monitor,
+/// with the index of the monitor appended to the id of each instance.
+fn instances_on_all_monitors(window_args: &WindowArguments) -> Vec<WindowArguments> {
+    let display = gdk::Display::default().expect("could not get default display");
+    (0..display.n_monitors())
+        .map(|index| WindowArguments {
+            instance_id: format!("{}-{}", window_args.instance_id, index),
+            monitor: Some(MonitorIdentifier::Numeric(index)),
+            ..window_args.clone()
+        })
+        .collect()
+}
+
+/// Get the rectangle a window currently covers, as reported by the window system.
+fn get_current_window_rectangle(window: &gtk::Window) -> gdk::Rectangle {
+    let (_, x, y) = window.window().map_or((0, 0, 0), |gdk_window| gdk_window.origin());
+    let (width, height) = window.size();
+    gdk::Rectangle::new(x, y, width, height)
+}
+
+/// Find the index of the monitor with the given geometry.
+fn get_monitor_index(monitor_geometry: gdk::Rectangle) -> Option<i32> {
+    let display = gdk::Display::default()?;
+    (0..display.n_monitors())
+        .find(|index| display.monitor(*index).map_or(false, |monitor| monitor.geometry() == monitor_geometry))
+}
+
+fn get_default_monitor_from_env() -> Option<MonitorIdentifier> {
+    let value = std::env::var(DEFAULT_MONITOR_ENV_VAR).ok()?;
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        value.parse().ok()
+    }
+}
+
 /// Returns the [Monitor][gdk::Monitor] structure corresponding to the identifer.
-/// Outside of x11, only [MonitorIdentifier::Numeric] is supported
+/// Names are matched against the model of the monitors, which is the output name on x11,
+/// and the model name reported by the compositor on wayland.
 pub fn get_monitor_from_display(display: &gdk::Display, identifier: &MonitorIdentifier) -> Option<gdk::Monitor> {
-    match identifier {
-        MonitorIdentifier::Numeric(num) => display.monitor(*num),
-
-        #[cfg(not(feature = "x11"))]
-        MonitorIdentifier::Name(_) => return None,
+    let monitor_models = (0..display.n_monitors())
+        .map(|m| display.monitor(m).and_then(|x| x.model()).map(|model| model.to_string()))
+        .collect_vec();
+    display.monitor(find_monitor_index(&monitor_models, identifier)?)
+}
 
-        #[cfg(feature = "x11")]
-        MonitorIdentifier::Name(name) => {
-            for m in 0..display.n_monitors() {
-                if let Some(model) = display.monitor(m).and_then(|x| x.model()) {
-                    if model == *name {
-                        return display.monitor(m);
-                    }
-                }
-            }
-            None
-        }
+/// Find the index of the monitor matching the identifier, given the models of all monitors in order.
+/// Returns `None` if no such monitor exists, instead of falling back to any other monitor.
+fn find_monitor_index(monitor_models: &[Option<String>], identifier: &MonitorIdentifier) -> Option<i32> {
+    match identifier {
+        MonitorIdentifier::Numeric(num) => (0..monitor_models.len() as i32).contains(num).then(|| *num),
+        MonitorIdentifier::Name(name) => monitor_models.iter().position(|model| model.as_ref() == Some(name)).map(|i| i as i32),
     }
 }
 
 pub fn get_window_rectangle(geometry: WindowGeometry, screen_rect: gdk::Rectangle) -> gdk::Rectangle {
     let (offset_x, offset_y) = geometry.offset.relative_to(screen_rect.width(), screen_rect.height());
     let (width, height) = geometry.size.relative_to(screen_rect.width(), screen_rect.height());
+    let width = geometry.max_width.map_or(width, |max| width.min(max.pixels_relative_to(screen_rect.width())));
+    let height = geometry.max_height.map_or(height, |max| height.min(max.pixels_relative_to(screen_rect.height())));
     let x = screen_rect.x() + offset_x + geometry.anchor_point.x.alignment_to_coordinate(width, screen_rect.width());
     let y = screen_rect.y() + offset_y + geometry.anchor_point.y.alignment_to_coordinate(height, screen_rect.height());
     gdk::Rectangle::new(x, y, width, height)
 }
+
+/// Adjust the offset of a geometry such that a window placed entirely outside of the monitor is moved back onto it.
+/// Returns `None` if at least part of the window is already within the monitor.
+pub fn clamp_geometry_to_monitor(geometry: WindowGeometry, monitor_geometry: gdk::Rectangle) -> Option<WindowGeometry> {
+    let rect = get_window_rectangle(geometry, monitor_geometry);
+    let overlaps =
+        |pos: i32, size: i32, monitor_pos: i32, monitor_size: i32| pos < monitor_pos + monitor_size && pos + size > monitor_pos;
+    let clamp = |pos: i32, size: i32, monitor_pos: i32, monitor_size: i32| {
+        pos.clamp(monitor_pos, monitor_pos.max(monitor_pos + monitor_size - size))
+    };
+    let (mon_x, mon_y, mon_width, mon_height) =
+        (monitor_geometry.x(), monitor_geometry.y(), monitor_geometry.width(), monitor_geometry.height());
+    if overlaps(rect.x(), rect.width(), mon_x, mon_width) && overlaps(rect.y(), rect.height(), mon_y, mon_height) {
+        return None;
+    }
+    let x = clamp(rect.x(), rect.width(), mon_x, mon_width);
+    let y = clamp(rect.y(), rect.height(), mon_y, mon_height);
+    let (offset_x, offset_y) = geometry.offset.relative_to(monitor_geometry.width(), monitor_geometry.height());
+    Some(WindowGeometry { offset: Coords::from_pixels((offset_x + x - rect.x(), offset_y + y - rect.y())), ..geometry })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_monitor_index() {
+        let monitor_models = vec![Some("DP-1".to_string()), None, Some("HDMI-A-1".to_string())];
+        assert_eq!(find_monitor_index(&monitor_models, &MonitorIdentifier::Numeric(1)), Some(1));
+        assert_eq!(find_monitor_index(&monitor_models, &MonitorIdentifier::Numeric(3)), None);
+        assert_eq!(find_monitor_index(&monitor_models, &MonitorIdentifier::Numeric(-1)), None);
+        assert_eq!(find_monitor_index(&monitor_models, &MonitorIdentifier::Name("HDMI-A-1".to_string())), Some(2));
+        assert_eq!(find_monitor_index(&monitor_models, &MonitorIdentifier::Name("DP-2".to_string())), None);
+    }
+
+    #[test]
+    fn test_coalesce_var_updates() {
+        let update = |name: &str, value: u32| DaemonCommand::UpdateVars(vec![(VarName::from(name), DynVal::from(value))]);
+        let commands =
+            (0..100).map(|value| update("foo", value)).chain([update("bar", 1), DaemonCommand::NoOp, update("foo", 0)]);
+        let coalesced = coalesce_var_updates(commands);
+        assert_eq!(coalesced.len(), 3);
+        match &coalesced[0] {
+            DaemonCommand::UpdateVars(mappings) => {
+                assert_eq!(mappings, &vec![(VarName::from("foo"), DynVal::from(99)), (VarName::from("bar"), DynVal::from(1))])
+            }
+            other => panic!("Expected UpdateVars, got {:?}", other),
+        }
+        assert!(matches!(coalesced[1], DaemonCommand::NoOp));
+        assert!(matches!(&coalesced[2], DaemonCommand::UpdateVars(mappings) if mappings.len() == 1));
+    }
+
+    #[test]
+    fn test_window_rectangle_relative_to_monitor() {
+        let geometry = WindowGeometry {
+            anchor_point: "bottom right".parse().unwrap(),
+            offset: "10x0".parse().unwrap(),
+            size: "50%x40".parse().unwrap(),
+            ..WindowGeometry::default()
+        };
+        let small_monitor = gdk::Rectangle::new(0, 0, 1920, 1080);
+        let large_monitor = gdk::Rectangle::new(1920, 0, 2560, 1440);
+        assert_eq!(get_window_rectangle(geometry, small_monitor), gdk::Rectangle::new(970, 1040, 960, 40));
+        assert_eq!(get_window_rectangle(geometry, large_monitor), gdk::Rectangle::new(3210, 1400, 1280, 40));
+    }
+
+    #[test]
+    fn test_with_minimum_window_size() {
+        assert_eq!(with_minimum_window_size(gdk::Rectangle::new(10, 20, 0, 0)), gdk::Rectangle::new(10, 20, 1, 1));
+        assert_eq!(with_minimum_window_size(gdk::Rectangle::new(10, 20, 30, 0)), gdk::Rectangle::new(10, 20, 30, 1));
+        assert_eq!(with_minimum_window_size(gdk::Rectangle::new(10, 20, 30, 40)), gdk::Rectangle::new(10, 20, 30, 40));
+    }
+
+    #[test]
+    fn test_slide_offset() {
+        let monitor = gdk::Rectangle::new(1920, 0, 1000, 500);
+        let window_rect = gdk::Rectangle::new(2020, 50, 200, 100);
+        assert_eq!(slide_offset(Side::Top, window_rect, monitor, 0.0), (0, -150));
+        assert_eq!(slide_offset(Side::Bottom, window_rect, monitor, 0.0), (0, 450));
+        assert_eq!(slide_offset(Side::Left, window_rect, monitor, 0.0), (-300, 0));
+        assert_eq!(slide_offset(Side::Right, window_rect, monitor, 0.0), (900, 0));
+        assert_eq!(slide_offset(Side::Right, window_rect, monitor, 0.5), (113, 0));
+        assert_eq!(slide_offset(Side::Top, window_rect, monitor, 1.0), (0, 0));
+    }
+
+    #[test]
+    fn test_window_rectangle_for_all_anchor_points() {
+        let monitor = gdk::Rectangle::new(100, 200, 1000, 500);
+        // Name and resulting coordinate of each alignment, for a positive and a negative offset
+        let x_alignments = [("left", 110, 90), ("center", 560, 540), ("right", 1010, 990)];
+        let y_alignments = [("top", 180, 220), ("center", 405, 445), ("bottom", 630, 670)];
+        for (x_name, x, x_with_negative_offset) in x_alignments {
+            for (y_name, y, y_with_negative_offset) in y_alignments {
+                let anchor_point = format!("{} {}", y_name, x_name);
+                let geometry = |offset: &str| WindowGeometry {
+                    anchor_point: anchor_point.parse().unwrap(),
+                    offset: offset.parse().unwrap(),
+                    size: "100x50".parse().unwrap(),
+                    ..WindowGeometry::default()
+                };
+                let rect = get_window_rectangle(geometry("10x-20"), monitor);
+                assert_eq!((rect.x(), rect.y()), (x, y), "anchored at {}", anchor_point);
+                let rect = get_window_rectangle(geometry("-10x20"), monitor);
+                let expected = (x_with_negative_offset, y_with_negative_offset);
+                assert_eq!((rect.x(), rect.y()), expected, "anchored at {} with negative x offset", anchor_point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clamp_geometry_to_monitor() {
+        let monitor = gdk::Rectangle::new(1920, 0, 1920, 1080);
+        let geometry = |offset: &str| WindowGeometry {
+            anchor_point: "top left".parse().unwrap(),
+            offset: offset.parse().unwrap(),
+            size: "100x50".parse().unwrap(),
+            ..WindowGeometry::default()
+        };
+        assert_eq!(clamp_geometry_to_monitor(geometry("10x10"), monitor), None);
+        assert_eq!(clamp_geometry_to_monitor(geometry("1850x-20"), monitor), None);
+
+        let clamped = clamp_geometry_to_monitor(geometry("5000x-100"), monitor).unwrap();
+        assert_eq!(get_window_rectangle(clamped, monitor), gdk::Rectangle::new(3740, 0, 100, 50));
+        let clamped = clamp_geometry_to_monitor(geometry("-200x500"), monitor).unwrap();
+        assert_eq!(get_window_rectangle(clamped, monitor), gdk::Rectangle::new(1920, 500, 100, 50));
+    }
+
+    /// Backend whose last initialization step fails, to check that the already created window isn't leaked then.
+    struct FailingBackend;
+
+    thread_local! {
+        static FAILING_BACKEND_WINDOW_DESTROYED: Cell<bool> = Cell::new(false);
+    }
+
+    impl DisplayBackend for FailingBackend {
+        fn initialize_window(_window_def: &WindowDefinition, _monitor: gdk::Rectangle) -> Option<gtk::Window> {
+            Some(gtk::Window::new(gtk::WindowType::Toplevel))
+        }
+
+        fn set_window_properties(window: &gtk::Window, _monitor: gdk::Rectangle, _window_def: &WindowDefinition) -> Result<()> {
+            window.connect_destroy(|_| FAILING_BACKEND_WINDOW_DESTROYED.with(|destroyed| destroyed.set(true)));
+            bail!("Injected backend error")
+        }
+    }
+
+    #[test]
+    fn test_initialize_window_closes_window_on_backend_error() {
+        // Creating windows requires a display, which isn't available everywhere the tests run.
+        if gtk::init().is_err() {
+            return;
+        }
+        let window_def = WindowDefinition::from_ast(yuck::parser::parse_string(0, "(defwindow test (box))").unwrap()).unwrap();
+        let window_args = WindowArguments::new_from_name("test".to_string());
+        let root_widget = gtk::Box::new(gtk::Orientation::Horizontal, 0).upcast();
+        let monitor_geometry = gdk::Rectangle::new(0, 0, 1920, 1080);
+        let result = initialize_window::<FailingBackend>(&window_args, monitor_geometry, root_widget, window_def, ScopeIndex(0));
+        assert!(result.is_err());
+
+        // Closing a window only takes effect once the main loop handles it.
+        while gtk::events_pending() {
+            gtk::main_iteration();
+        }
+        assert!(FAILING_BACKEND_WINDOW_DESTROYED.with(Cell::get));
+    }
+}
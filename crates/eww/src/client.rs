@@ -2,6 +2,7 @@ use std::process::Stdio;
 
 use crate::{
     daemon_response::DaemonResponse,
+    error_handling_ctx,
     opts::{self, ActionClientOnly},
     paths::EwwPaths,
 };
@@ -20,24 +21,46 @@ pub fn handle_client_only_action(paths: &EwwPaths, action: ActionClientOnly) ->
                 .spawn()?
                 .wait()?;
         }
+        ActionClientOnly::Validate => {
+            let errors = validate_config(paths);
+            if errors.is_empty() {
+                println!("The configuration in {} is valid", paths.get_config_dir().display());
+            } else {
+                for err in &errors {
+                    eprintln!("{}", error_handling_ctx::format_error(err));
+                }
+                std::process::exit(1);
+            }
+        }
     }
     Ok(())
 }
 
+/// Check the configuration and stylesheet, collecting the errors of every top-level definition and every CSS error.
+/// SCSS compilation stops at its first error, so only that one is reported in that case.
+/// This only reads the configuration files, and never talks to the daemon.
+fn validate_config(paths: &EwwPaths) -> Vec<anyhow::Error> {
+    let mut errors = crate::config::validate_eww_paths(paths);
+    match crate::config::scss::parse_scss_from_config(paths.get_config_dir()) {
+        // GTK can only check the resulting CSS if it can be initialized, which requires a display.
+        Ok((file_id, css)) => {
+            if gtk::init().is_ok() {
+                errors.extend(crate::app::load_css_collecting_errors(&gtk::CssProvider::new(), file_id, &css));
+            }
+        }
+        Err(err) => errors.push(err),
+    }
+    errors
+}
+
 /// Connect to the daemon and send the given request.
 /// Returns the response from the daemon, or None if the daemon did not provide any useful response. An Ok(None) response does _not_ indicate failure.
 pub fn do_server_call(stream: &mut UnixStream, action: &opts::ActionWithServer) -> Result<Option<DaemonResponse>> {
     log::debug!("Forwarding options to server");
-    stream.set_nonblocking(false).context("Failed to set stream to non-blocking")?;
-
-    let message_bytes = bincode::serialize(&action)?;
-
-    stream.write(&(message_bytes.len() as u32).to_be_bytes()).context("Failed to send command size header to IPC stream")?;
-
-    stream.write_all(&message_bytes).context("Failed to write command to IPC stream")?;
+    write_action(stream, action)?;
 
     let mut buf = Vec::new();
-    stream.set_read_timeout(Some(std::time::Duration::from_millis(100))).context("Failed to set read timeout")?;
+    stream.set_read_timeout(action.response_timeout()).context("Failed to set read timeout")?;
     stream.read_to_end(&mut buf).context("Error reading response from server")?;
 
     Ok(if buf.is_empty() {
@@ -47,3 +70,35 @@ pub fn do_server_call(stream: &mut UnixStream, action: &opts::ActionWithServer)
         Some(buf)
     })
 }
+
+/// Connect to the daemon and send a streaming request, see [`opts::ActionWithServer::is_streaming`].
+/// Every response of the daemon is passed to the given function, until the daemon closes the connection.
+pub fn do_streaming_server_call(
+    stream: &mut UnixStream,
+    action: &opts::ActionWithServer,
+    mut on_response: impl FnMut(DaemonResponse),
+) -> Result<()> {
+    write_action(stream, action)?;
+    loop {
+        let mut response_byte_length = [0u8; 4];
+        match stream.read_exact(&mut response_byte_length) {
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            result => result.context("Error reading response size from server")?,
+        }
+        let mut buf = vec![0u8; u32::from_be_bytes(response_byte_length) as usize];
+        stream.read_exact(&mut buf).context("Error reading response from server")?;
+        on_response(bincode::deserialize(&buf)?);
+    }
+}
+
+/// Send an action to the daemon, prefixed with its size.
+fn write_action(stream: &mut UnixStream, action: &opts::ActionWithServer) -> Result<()> {
+    stream.set_nonblocking(false).context("Failed to set stream to non-blocking")?;
+
+    let message_bytes = bincode::serialize(&action)?;
+
+    stream.write(&(message_bytes.len() as u32).to_be_bytes()).context("Failed to send command size header to IPC stream")?;
+
+    stream.write_all(&message_bytes).context("Failed to write command to IPC stream")?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Recording of the actions the daemon receives via IPC, such that they can be replayed later.
+//!
+//! While a recording is active, every received action is appended to the recording file as a line of JSON,
+//! together with the time that passed since the previous action. Recordings are replayed via `eww replay`.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Instant,
+};
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+
+use crate::opts::ActionWithServer;
+
+static ACTIVE_RECORDING: Lazy<Mutex<Option<Recording>>> = Lazy::new(|| Mutex::new(None));
+
+struct Recording {
+    path: PathBuf,
+    file: File,
+    last_action_time: Instant,
+}
+
+/// A single action read from a recording.
+#[derive(Debug, PartialEq, serde::Deserialize)]
+pub struct RecordedAction {
+    /// Milliseconds between the previous action (or the start of the recording) and this one
+    pub delay_ms: u64,
+    pub action: ActionWithServer,
+}
+
+/// Start recording into the given file, replacing its contents. Fails if a recording is already active.
+pub fn start(path: &Path) -> Result<()> {
+    let mut recording = ACTIVE_RECORDING.lock().unwrap();
+    if let Some(recording) = recording.as_ref() {
+        bail!("Already recording into {}", recording.path.display());
+    }
+    let file = File::create(path).with_context(|| format!("Failed to create recording file {}", path.display()))?;
+    *recording = Some(Recording { path: path.to_path_buf(), file, last_action_time: Instant::now() });
+    Ok(())
+}
+
+/// Stop the active recording, returning the path of the file it was written to.
+pub fn stop() -> Result<PathBuf> {
+    match ACTIVE_RECORDING.lock().unwrap().take() {
+        Some(recording) => Ok(recording.path),
+        None => bail!("No recording is active"),
+    }
+}
+
+/// Add an action to the active recording, if there is one.
+/// Actions that control recordings are never recorded themselves.
+pub fn record(action: &ActionWithServer) {
+    let is_recording_action = matches!(
+        action,
+        ActionWithServer::StartRecording { .. } | ActionWithServer::StopRecording | ActionWithServer::Replay { .. }
+    );
+    if is_recording_action {
+        return;
+    }
+    let mut recording = ACTIVE_RECORDING.lock().unwrap();
+    if let Some(recording) = recording.as_mut() {
+        let now = Instant::now();
+        let delay_ms = now.duration_since(recording.last_action_time).as_millis() as u64;
+        recording.last_action_time = now;
+        let result: Result<()> = try {
+            let line = serde_json::to_string(&serde_json::json!({ "delay_ms": delay_ms, "action": action }))?;
+            writeln!(recording.file, "{}", line)?;
+        };
+        crate::print_result_err!("writing action to recording", result);
+    }
+}
+
+/// Read all actions of a recording file.
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedAction>> {
+    let file = File::open(path).with_context(|| format!("Failed to open recording file {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => {
+                let action = serde_json::from_str::<RecordedAction>(&line);
+                Some(action.with_context(|| format!("Invalid action in line {} of {}", index + 1, path.display())))
+            }
+            Err(err) => Some(Err(err.into())),
+        })
+        .collect()
+}
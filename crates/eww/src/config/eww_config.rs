@@ -1,10 +1,11 @@
 use anyhow::{bail, Context, Result};
 use eww_shared_util::VarName;
+use itertools::Itertools;
 use std::collections::HashMap;
 use yuck::{
     config::{
-        script_var_definition::ScriptVarDefinition, validate::ValidationError, widget_definition::WidgetDefinition,
-        window_definition::WindowDefinition, Config,
+        script_var_definition::ScriptVarDefinition, validate::ValidationError, var_definition::DerivedVarDefinition,
+        widget_definition::WidgetDefinition, window_definition::WindowDefinition, Config,
     },
     error::DiagError,
     format_diagnostic::ToDiagnostic,
@@ -12,7 +13,10 @@ use yuck::{
 
 use simplexpr::dynval::DynVal;
 
-use crate::{config::inbuilt, error_handling_ctx, file_database::FileDatabase, paths::EwwPaths, widgets::widget_definitions};
+use crate::{
+    config::inbuilt, daemon_response::ErrorKind, error_handling_ctx, file_database::FileDatabase, paths::EwwPaths,
+    widgets::widget_definitions,
+};
 
 use super::script_var;
 
@@ -23,16 +27,56 @@ pub fn read_from_eww_paths(eww_paths: &EwwPaths) -> Result<EwwConfig> {
     EwwConfig::read_from_dir(&mut error_handling_ctx::FILE_DATABASE.write().unwrap(), eww_paths)
 }
 
+/// Check the configuration in the config dir of the given [`crate::EwwPaths`] like [`read_from_eww_paths`] does,
+/// but report the errors of every top-level definition instead of stopping at the first one.
+pub fn validate_eww_paths(eww_paths: &EwwPaths) -> Vec<anyhow::Error> {
+    error_handling_ctx::clear_files();
+    let mut files = error_handling_ctx::FILE_DATABASE.write().unwrap();
+    let yuck_path = eww_paths.get_yuck_path();
+    if !yuck_path.exists() {
+        return vec![anyhow::anyhow!("The configuration file `{}` does not exist", yuck_path.display())];
+    }
+    match Config::generate_from_main_file_collecting_errors(&mut *files, yuck_path) {
+        Ok((config, errors)) => errors.into_iter().map(anyhow::Error::from).chain(validation_errors(&config)).collect(),
+        Err(err) => vec![err.into()],
+    }
+}
+
+/// Run all validations on a configuration, returning the first error of every definition.
+fn validation_errors(config: &Config) -> Vec<anyhow::Error> {
+    let magic_globals: Vec<_> = inbuilt::INBUILT_VAR_NAMES
+        .iter()
+        .chain(inbuilt::MAGIC_CONSTANT_NAMES)
+        .chain(inbuilt::WINDOW_MAGIC_VAR_NAMES)
+        .into_iter()
+        .map(|x| VarName::from(*x))
+        .collect();
+    let mut errors =
+        yuck::config::validate::validate_all(config, magic_globals).into_iter().map(anyhow::Error::from).collect_vec();
+    for (name, def) in &config.widget_definitions {
+        if widget_definitions::BUILTIN_WIDGET_NAMES.contains(&name.as_str()) {
+            errors.push(DiagError(ValidationError::AccidentalBuiltinOverride(def.span, name.to_string()).to_diagnostic()).into());
+        }
+    }
+    errors
+}
+
 /// Eww configuration structure.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct EwwConfig {
     widgets: HashMap<String, WidgetDefinition>,
     windows: HashMap<String, WindowDefinition>,
     initial_variables: HashMap<VarName, DynVal>,
     script_vars: HashMap<VarName, ScriptVarDefinition>,
+    derived_vars: HashMap<VarName, DerivedVarDefinition>,
 
     // map of variables to all pollvars which refer to them in their run-while-expression
+    #[serde(skip)]
     run_while_mentions: HashMap<VarName, Vec<VarName>>,
+
+    // map of variables to all derived variables which refer to them in their expression
+    #[serde(skip)]
+    derived_var_mentions: HashMap<VarName, Vec<VarName>>,
 }
 
 impl EwwConfig {
@@ -43,25 +87,11 @@ impl EwwConfig {
             bail!("The configuration file `{}` does not exist", yuck_path.display());
         }
         let config = Config::generate_from_main_file(files, yuck_path)?;
-
-        // run some validations on the configuration
-        let magic_globals: Vec<_> = inbuilt::INBUILT_VAR_NAMES
-            .iter()
-            .chain(inbuilt::MAGIC_CONSTANT_NAMES)
-            .into_iter()
-            .map(|x| VarName::from(*x))
-            .collect();
-        yuck::config::validate::validate(&config, magic_globals)?;
-
-        for (name, def) in &config.widget_definitions {
-            if widget_definitions::BUILTIN_WIDGET_NAMES.contains(&name.as_str()) {
-                return Err(
-                    DiagError(ValidationError::AccidentalBuiltinOverride(def.span, name.to_string()).to_diagnostic()).into()
-                );
-            }
+        if let Some(err) = validation_errors(&config).into_iter().next() {
+            return Err(err);
         }
 
-        let Config { widget_definitions, window_definitions, mut var_definitions, mut script_vars } = config;
+        let Config { widget_definitions, window_definitions, mut var_definitions, derived_vars, mut script_vars } = config;
         script_vars.extend(inbuilt::get_inbuilt_vars());
         var_definitions.extend(inbuilt::get_magic_constants(eww_paths));
 
@@ -74,44 +104,106 @@ impl EwwConfig {
             }
         }
 
+        let mut derived_var_mentions = HashMap::<VarName, Vec<VarName>>::new();
+        for var in derived_vars.values() {
+            for name in var.expr.collect_var_refs().into_iter().unique() {
+                derived_var_mentions.entry(name).or_default().push(var.name.clone())
+            }
+        }
+
         Ok(EwwConfig {
             windows: window_definitions,
             widgets: widget_definitions,
             initial_variables: var_definitions.into_iter().map(|(k, v)| (k, v.initial_value)).collect(),
             script_vars,
+            derived_vars,
             run_while_mentions,
+            derived_var_mentions,
         })
     }
 
-    // TODO this is kinda ugly
-    pub fn generate_initial_state(&self) -> Result<HashMap<VarName, DynVal>> {
+    /// Generate the initial state, but use the given values for script-vars and variables rather than their initial values.
+    /// Derived variables are always evaluated from the resulting values.
+    pub fn generate_state_keeping(&self, mut kept_values: HashMap<VarName, DynVal>) -> Result<HashMap<VarName, DynVal>> {
         let mut vars = self
             .script_vars
             .iter()
-            .map(|(name, var)| Ok((name.clone(), script_var::initial_value(var)?)))
+            .map(|(name, var)| match kept_values.remove(name) {
+                Some(value) => Ok((name.clone(), value)),
+                None => Ok((name.clone(), script_var::initial_value(var)?)),
+            })
             .collect::<Result<HashMap<_, _>>>()?;
-        vars.extend(self.initial_variables.clone());
+        vars.extend(
+            self.initial_variables
+                .iter()
+                .map(|(name, value)| (name.clone(), kept_values.remove(name).unwrap_or_else(|| value.clone()))),
+        );
+        self.evaluate_derived_vars(&mut vars);
         Ok(vars)
     }
 
+    /// Evaluate all derived variables given the values of the other variables,
+    /// evaluating each derived variable after the derived variables it depends on.
+    /// Derived variables that fail to evaluate start out empty.
+    fn evaluate_derived_vars(&self, vars: &mut HashMap<VarName, DynVal>) {
+        let mut remaining: Vec<&DerivedVarDefinition> = self.derived_vars.values().collect();
+        while !remaining.is_empty() {
+            let (ready, pending): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|var| {
+                var.expr.collect_var_refs().iter().all(|name| !self.derived_vars.contains_key(name) || vars.contains_key(name))
+            });
+            // Cycles are rejected when validating the configuration, so this only guards against looping forever.
+            let (ready, pending) = if ready.is_empty() { (pending, Vec::new()) } else { (ready, pending) };
+            for var in ready {
+                let value = var.expr.eval(vars).unwrap_or_else(|err| {
+                    error_handling_ctx::print_error(anyhow::anyhow!(err));
+                    DynVal::from_string(String::new())
+                });
+                vars.insert(var.name.clone(), value);
+            }
+            remaining = pending;
+        }
+    }
+
+    /// Whether this configuration defines the same widgets and variables as the other one,
+    /// meaning that the two only differ in their window definitions.
+    pub fn has_same_state_as(&self, other: &EwwConfig) -> bool {
+        self.widgets == other.widgets
+            && self.initial_variables == other.initial_variables
+            && self.script_vars == other.script_vars
+            && self.derived_vars == other.derived_vars
+    }
+
     pub fn get_windows(&self) -> &HashMap<String, WindowDefinition> {
         &self.windows
     }
 
     pub fn get_window(&self, name: &str) -> Result<&WindowDefinition> {
-        self.windows.get(name).with_context(|| {
-            format!(
+        self.windows.get(name).ok_or_else(|| {
+            ErrorKind::NoSuchWindow.error(format!(
                 "No window named '{}' exists in config.\nThis may also be caused by your config failing to load properly, \
                  please check for any other errors in that case.",
                 name
-            )
+            ))
         })
     }
 
+    /// The initial values of all variables defined via `defvar`, including the magic constants.
+    pub fn get_initial_variables(&self) -> &HashMap<VarName, DynVal> {
+        &self.initial_variables
+    }
+
+    pub fn get_script_vars(&self) -> &HashMap<VarName, ScriptVarDefinition> {
+        &self.script_vars
+    }
+
     pub fn get_script_var(&self, name: &VarName) -> Result<&ScriptVarDefinition> {
         self.script_vars.get(name).with_context(|| format!("No script var named '{}' exists", name))
     }
 
+    pub fn get_derived_var(&self, name: &VarName) -> Result<&DerivedVarDefinition> {
+        self.derived_vars.get(name).with_context(|| format!("No derived variable named '{}' exists", name))
+    }
+
     pub fn get_widget_definitions(&self) -> &HashMap<String, WidgetDefinition> {
         &self.widgets
     }
@@ -120,4 +212,9 @@ impl EwwConfig {
     pub fn get_run_while_mentions_of(&self, name: &VarName) -> Option<&Vec<VarName>> {
         self.run_while_mentions.get(name)
     }
+
+    /// Given a variable name, get the names of all derived variables whose expression references that variable
+    pub fn get_derived_var_mentions_of(&self, name: &VarName) -> Option<&Vec<VarName>> {
+        self.derived_var_mentions.get(name)
+    }
 }
@@ -7,10 +7,25 @@ use yuck::config::{
 };
 
 use crate::{config::system_stats::*, paths::EwwPaths};
-use eww_shared_util::VarName;
+use eww_shared_util::{AttrName, VarName};
+
+/// Environment variable of the daemon setting the interval in which `EWW_TIME` is updated, in seconds.
+pub const TIME_INTERVAL_ENV_VAR: &str = "EWW_TIME_INTERVAL";
+
+/// The interval in which `EWW_TIME` is updated, as set in [`TIME_INTERVAL_ENV_VAR`], defaulting to one second.
+fn get_time_interval() -> u64 {
+    match std::env::var(TIME_INTERVAL_ENV_VAR).ok().map(|interval| interval.trim().parse::<u64>()) {
+        Some(Ok(interval)) if interval > 0 => interval,
+        Some(_) => {
+            log::warn!("{} must be a positive amount of seconds, using an interval of 1s instead", TIME_INTERVAL_ENV_VAR);
+            1
+        }
+        None => 1,
+    }
+}
 
 macro_rules! define_builtin_vars {
-    ($($name:literal [$interval:literal] => $fun:expr),*$(,)?) => {
+    ($($name:literal [$interval:expr] => $fun:expr),*$(,)?) => {
         pub static INBUILT_VAR_NAMES: &[&'static str] = &[$($name),*];
         pub fn get_inbuilt_vars() -> HashMap<VarName, ScriptVarDefinition> {
             maplit::hashmap! {
@@ -62,8 +77,8 @@ define_builtin_vars! {
     // @prop { <name>: { up, down } }
     "EWW_NET" [2] => || Ok(DynVal::from(net())) ,
 
-    // @desc EWW_TIME - the current UNIX timestamp
-    "EWW_TIME" [1] => || Ok(DynVal::from(get_time())) ,
+    // @desc EWW_TIME - the current UNIX timestamp, updated every second or every `EWW_TIME_INTERVAL` seconds, if set
+    "EWW_TIME" [get_time_interval()] => || Ok(DynVal::from(get_time())) ,
 }
 
 macro_rules! define_magic_constants {
@@ -96,3 +111,27 @@ define_magic_constants! { eww_paths,
         std::env::current_exe().map(|x| x.to_string_lossy().into_owned()).unwrap_or_else(|_| "eww".to_string()),
     ),
 }
+
+macro_rules! define_window_magic_vars {
+    ($monitor_geometry:ident, $($name:literal => $value:expr),*$(,)?) => {
+        pub static WINDOW_MAGIC_VAR_NAMES: &[&'static str] = &[$($name),*];
+        pub fn get_window_magic_vars($monitor_geometry: gdk::Rectangle) -> HashMap<AttrName, SimplExpr> {
+            maplit::hashmap! {
+                $(AttrName::from($name) => SimplExpr::Literal(DynVal::from($value))),*
+            }
+        }
+    }
+}
+define_window_magic_vars! { monitor_geometry,
+    // @desc EWW_MONITOR_WIDTH - Width of the monitor the current window is opened on, in pixels
+    "EWW_MONITOR_WIDTH" => monitor_geometry.width(),
+
+    // @desc EWW_MONITOR_HEIGHT - Height of the monitor the current window is opened on, in pixels
+    "EWW_MONITOR_HEIGHT" => monitor_geometry.height(),
+
+    // @desc EWW_MONITOR_X - Horizontal position of the monitor the current window is opened on, in pixels
+    "EWW_MONITOR_X" => monitor_geometry.x(),
+
+    // @desc EWW_MONITOR_Y - Vertical position of the monitor the current window is opened on, in pixels
+    "EWW_MONITOR_Y" => monitor_geometry.y(),
+}
@@ -1,5 +1,6 @@
 use std::process::Command;
 
+use crate::process_output::{self, OutputStream};
 use anyhow::{anyhow, bail, Context, Result};
 use codespan_reporting::diagnostic::Severity;
 use eww_shared_util::{Span, VarName};
@@ -27,9 +28,8 @@ pub fn initial_value(var: &ScriptVarDefinition) -> Result<DynVal> {
                 VarSource::Function(f) => f()
                     .map_err(|err| anyhow!(err))
                     .with_context(|| format!("Failed to compute initial value for {}", &var.name())),
-                VarSource::Shell(span, command) => {
-                    run_command(command).map_err(|e| anyhow!(create_script_var_failed_warn(*span, var.name(), &e.to_string())))
-                }
+                VarSource::Shell(span, command) => run_command(var.name(), command)
+                    .map_err(|e| anyhow!(create_script_var_failed_warn(*span, var.name(), &e.to_string()))),
             },
         },
 
@@ -37,10 +37,11 @@ pub fn initial_value(var: &ScriptVarDefinition) -> Result<DynVal> {
     }
 }
 
-/// Run a command and get the output
-pub fn run_command(cmd: &str) -> Result<DynVal> {
+/// Run the command of a script-var and get the output. Anything written to stderr is recorded in the [`process_output`].
+pub fn run_command(var_name: &VarName, cmd: &str) -> Result<DynVal> {
     log::debug!("Running command: {}", cmd);
     let command = Command::new("/bin/sh").arg("-c").arg(cmd).output()?;
+    process_output::record_all(&format!("var {}", var_name), OutputStream::Stderr, &String::from_utf8_lossy(&command.stderr));
     if !command.status.success() {
         bail!("Failed with output:\n{}", String::from_utf8(command.stderr)?);
     }
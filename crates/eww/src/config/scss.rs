@@ -1,9 +1,12 @@
-use std::path::Path;
+use std::{ops::Range, path::Path};
 
 use anyhow::{anyhow, Context};
 
 use crate::{error_handling_ctx, util::replace_env_var_references};
 
+/// Stylesheet embedded in the binary, providing defaults below the stylesheet of the configuration.
+pub const DEFAULT_CSS: &str = include_str!("default.css");
+
 /// read an (s)css file, replace all environment variable references within it and
 /// then parse it into css.
 /// Also adds the CSS to the [`crate::file_database::FileDatabase`]
@@ -32,3 +35,104 @@ pub fn parse_scss_from_config(path: &Path) -> anyhow::Result<(usize, String)> {
     let file_id = file_db.insert_string(s_css_path.display().to_string(), css.clone())?;
     Ok((file_id, css))
 }
+
+/// A stylesheet, split into its GTK color definitions (`@define-color name value;`) and the remaining CSS.
+///
+/// This allows detecting changes that only affect the color definitions,
+/// which can be applied without reloading the whole stylesheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitStylesheet {
+    pub color_definitions: Vec<(String, String)>,
+    pub rest: String,
+}
+
+impl SplitStylesheet {
+    pub fn from_css(css: &str) -> Self {
+        let pattern = regex!(r"@define-color\s+([\w-]+)\s+([^;]+);");
+        let ignored_ranges = comment_and_string_ranges(css);
+        let definitions = pattern
+            .captures_iter(css)
+            .filter(|captures| {
+                let start = captures.get(0).unwrap().start();
+                !ignored_ranges.iter().any(|range| range.contains(&start))
+            })
+            .collect::<Vec<_>>();
+
+        let color_definitions =
+            definitions.iter().map(|captures| (captures[1].to_string(), captures[2].trim().to_string())).collect();
+        let mut rest = String::new();
+        let mut last_end = 0;
+        for captures in &definitions {
+            let definition = captures.get(0).unwrap();
+            rest.push_str(&css[last_end..definition.start()]);
+            last_end = definition.end();
+        }
+        rest.push_str(&css[last_end..]);
+        Self { color_definitions, rest }
+    }
+
+    /// CSS containing only the color definitions of this stylesheet.
+    pub fn color_definitions_css(&self) -> String {
+        self.color_definitions.iter().map(|(name, value)| format!("@define-color {} {};\n", name, value)).collect()
+    }
+}
+
+/// Byte ranges of the comments and strings in some CSS, in which `@define-color` does not define a color.
+fn comment_and_string_ranges(css: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut chars = css.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let end = match c {
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut previous = ' ';
+                chars.find(|&(_, next)| std::mem::replace(&mut previous, next) == '*' && next == '/')
+            }
+            '"' | '\'' => {
+                let mut escaped = false;
+                chars.find(|&(_, next)| {
+                    let closes = !escaped && next == c;
+                    escaped = !escaped && next == '\\';
+                    closes
+                })
+            }
+            _ => continue,
+        };
+        ranges.push(start..end.map_or(css.len(), |(index, _)| index + 1));
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_stylesheet() {
+        let css = "@define-color accent #ff0000;\n.bar { color: @accent; }\n@define-color bg rgba(0, 0, 0, 0.5);";
+        let stylesheet = SplitStylesheet::from_css(css);
+        assert_eq!(
+            stylesheet.color_definitions,
+            vec![("accent".to_string(), "#ff0000".to_string()), ("bg".to_string(), "rgba(0, 0, 0, 0.5)".to_string())]
+        );
+        assert_eq!(stylesheet.rest, "\n.bar { color: @accent; }\n");
+        assert_eq!(stylesheet.color_definitions_css(), "@define-color accent #ff0000;\n@define-color bg rgba(0, 0, 0, 0.5);\n");
+
+        let recolored =
+            SplitStylesheet::from_css("@define-color accent #00ff00;\n.bar { color: @accent; }\n@define-color bg black;");
+        assert_eq!(stylesheet.rest, recolored.rest);
+    }
+
+    #[test]
+    fn test_split_stylesheet_ignores_comments_and_strings() {
+        let css = "/* @define-color accent #ff0000; */\n.bar { content: \"@define-color bg black;\"; }\n@define-color fg white;";
+        let stylesheet = SplitStylesheet::from_css(css);
+        assert_eq!(stylesheet.color_definitions, vec![("fg".to_string(), "white".to_string())]);
+        assert_eq!(stylesheet.rest, "/* @define-color accent #ff0000; */\n.bar { content: \"@define-color bg black;\"; }\n");
+
+        // Changing a commented-out definition is not a color-only change
+        let changed = SplitStylesheet::from_css(&css.replace("#ff0000", "#00ff00"));
+        assert_ne!(stylesheet.rest, changed.rest);
+        assert_eq!(comment_and_string_ranges("a /* b */ 'c\\'d' \"e"), vec![2..9, 10..16, 17..19]);
+    }
+}
@@ -4,10 +4,15 @@
 //! If the daemon needs to send messages back to the client as a response to a command (mostly for CLI output),
 //! this happens via the DaemonResponse types
 
+use std::sync::Arc;
+
 use anyhow::{Context, Result};
 use itertools::Itertools;
+use once_cell::sync::OnceCell;
 use tokio::sync::mpsc;
 
+use yuck::{config::validate::ValidationError, error::DiagError};
+
 use crate::error_handling_ctx;
 
 /// Response that the app may send as a response to a event.
@@ -15,33 +20,111 @@ use crate::error_handling_ctx;
 #[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, derive_more::Display)]
 pub enum DaemonResponse {
     Success(String),
-    Failure(String),
+    #[display(fmt = "{}", message)]
+    Failure {
+        message: String,
+        kind: ErrorKind,
+    },
+}
+
+/// What kind of error caused a [`DaemonResponse::Failure`],
+/// allowing clients to react to specific failures without parsing the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, derive_more::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    #[display(fmt = "no_such_window")]
+    NoSuchWindow,
+    #[display(fmt = "no_such_variable")]
+    NoSuchVariable,
+    #[display(fmt = "config_error")]
+    ConfigError,
+    #[display(fmt = "io")]
+    Io,
+    #[display(fmt = "other")]
+    Other,
+}
+
+impl ErrorKind {
+    /// Create an error of this kind with the given message.
+    pub fn error(self, message: impl Into<String>) -> anyhow::Error {
+        KindedError { kind: self, message: message.into() }.into()
+    }
+
+    /// Determine the kind of an error from the errors in its chain.
+    pub fn of(err: &anyhow::Error) -> Self {
+        if let Some(kinded) = err.chain().find_map(|e| e.downcast_ref::<KindedError>()) {
+            kinded.kind
+        } else if err.chain().any(|e| e.is::<DiagError>() || e.is::<ValidationError>()) {
+            ErrorKind::ConfigError
+        } else if err.chain().any(|e| e.is::<std::io::Error>()) {
+            ErrorKind::Io
+        } else {
+            ErrorKind::Other
+        }
+    }
 }
 
-#[derive(Debug)]
-pub struct DaemonResponseSender(mpsc::UnboundedSender<DaemonResponse>);
+/// An error with an explicitly given [`ErrorKind`], see [`ErrorKind::error`].
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+struct KindedError {
+    kind: ErrorKind,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DaemonResponseSender {
+    sender: mpsc::UnboundedSender<DaemonResponse>,
+    /// Name of the command this responds to, shared between all clones of the sender. See [`Self::set_command_name`].
+    command_name: Arc<OnceCell<&'static str>>,
+}
 
 pub fn create_pair() -> (DaemonResponseSender, mpsc::UnboundedReceiver<DaemonResponse>) {
     let (sender, recv) = mpsc::unbounded_channel();
-    (DaemonResponseSender(sender), recv)
+    (DaemonResponseSender { sender, command_name: Arc::new(OnceCell::new()) }, recv)
 }
 
 impl DaemonResponseSender {
+    /// Set the name of the command this sender responds to, under which failure responses are recorded in the error history.
+    /// Only the first name that is set is kept.
+    pub fn set_command_name(&self, name: &'static str) {
+        let _ = self.command_name.set(name);
+    }
+
     pub fn send_success(&self, s: String) -> Result<()> {
-        self.0.send(DaemonResponse::Success(s)).context("Failed to send success response from application thread")
+        self.sender.send(DaemonResponse::Success(s)).context("Failed to send success response from application thread")
     }
 
     pub fn send_failure(&self, s: String) -> Result<()> {
-        self.0.send(DaemonResponse::Failure(s)).context("Failed to send failure response from application thread")
+        self.send_failure_of_kind(ErrorKind::Other, s)
+    }
+
+    /// Send a failure response, recording it in the error history.
+    pub fn send_failure_of_kind(&self, kind: ErrorKind, s: String) -> Result<()> {
+        error_handling_ctx::record_error(self.command_name.get().copied().unwrap_or("unknown command"), kind, s.clone());
+        self.sender
+            .send(DaemonResponse::Failure { message: s, kind })
+            .context("Failed to send failure response from application thread")
+    }
+
+    /// Returns true if the receiving end has been dropped, i.e. because the client stopped waiting for a response.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
     }
 
     /// Given a list of errors, respond with an error value if there are any errors, and respond with success otherwise.
+    /// The kind of the failure is the kind shared by all errors, or [`ErrorKind::Other`] if they differ.
     pub fn respond_with_error_list(&self, errors: impl IntoIterator<Item = anyhow::Error>) -> Result<()> {
-        let errors = errors.into_iter().map(|e| error_handling_ctx::format_error(&e)).join("\n");
+        let errors = errors.into_iter().collect::<Vec<_>>();
+        let kind = match errors.iter().map(ErrorKind::of).dedup().exactly_one() {
+            Ok(kind) => kind,
+            Err(_) => ErrorKind::Other,
+        };
+        let errors = errors.iter().map(error_handling_ctx::format_error).join("\n");
         if errors.is_empty() {
             self.send_success(String::new())
         } else {
-            self.respond_with_error_msg(errors)
+            self.respond_with_error_msg(kind, errors)
         }
     }
 
@@ -51,15 +134,15 @@ impl DaemonResponseSender {
             Ok(_) => self.send_success(String::new()),
             Err(e) => {
                 let formatted = error_handling_ctx::format_error(&e);
-                self.respond_with_error_msg(formatted)
+                self.respond_with_error_msg(ErrorKind::of(&e), formatted)
             }
         }
         .context("sending response from main thread")
     }
 
-    fn respond_with_error_msg(&self, msg: String) -> Result<()> {
+    fn respond_with_error_msg(&self, kind: ErrorKind, msg: String) -> Result<()> {
         println!("Action failed with error: {}", msg);
-        self.send_failure(msg)
+        self.send_failure_of_kind(kind, msg)
     }
 }
 
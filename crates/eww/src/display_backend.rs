@@ -1,4 +1,4 @@
-use yuck::config::window_definition::WindowDefinition;
+use yuck::config::{window_definition::WindowDefinition, window_geometry::WindowGeometry};
 
 #[cfg(feature = "wayland")]
 pub use platform_wayland::WaylandBackend;
@@ -8,6 +8,39 @@ pub use platform_x11::{set_xprops, X11Backend};
 
 pub trait DisplayBackend: Send + Sync + 'static {
     fn initialize_window(window_def: &WindowDefinition, monitor: gdk::Rectangle) -> Option<gtk::Window>;
+
+    /// Whether windows created from the given definition can receive keyboard focus.
+    fn is_focusable(_window_def: &WindowDefinition) -> bool {
+        true
+    }
+
+    /// Move an already initialized window to the position given by the geometry.
+    /// Backends that position windows by moving their gdk window, like x11, don't need to do anything here.
+    fn apply_position(_window: &gtk::Window, _geometry: WindowGeometry, _monitor: gdk::Rectangle) {}
+
+    /// Set the namespace of a window, which compositors may use in their rules. This needs to happen before it is realized.
+    /// Backends without a concept of namespaces don't need to do anything here.
+    fn set_namespace(_window: &gtk::Window, _namespace: &str) {}
+
+    /// Set the properties of a window that can only be set once it has been realized, like the struts on x11.
+    fn set_window_properties(
+        _window: &gtk::Window,
+        _monitor: gdk::Rectangle,
+        _window_def: &WindowDefinition,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Let clicks and other pointer input pass through the window to the windows below it, by giving it an empty input region.
+    /// GTK maps the input region to the shape extension on x11 and to the surface input region on wayland.
+    fn set_input_passthrough(window: &gtk::Window, passthrough: bool) {
+        use gtk::prelude::WidgetExt;
+        if passthrough {
+            window.input_shape_combine_region(Some(&cairo::Region::create()));
+        } else {
+            window.input_shape_combine_region(None);
+        }
+    }
 }
 
 pub struct NoBackend;
@@ -23,7 +56,7 @@ mod platform_wayland {
     use gtk::prelude::*;
     use yuck::config::{
         window_definition::{WindowDefinition, WindowStacking},
-        window_geometry::AnchorAlignment,
+        window_geometry::{AnchorAlignment, WindowGeometry},
     };
 
     use super::DisplayBackend;
@@ -31,6 +64,10 @@ mod platform_wayland {
     pub struct WaylandBackend;
 
     impl DisplayBackend for WaylandBackend {
+        fn is_focusable(window_def: &WindowDefinition) -> bool {
+            window_def.backend_options.wayland.focusable
+        }
+
         fn initialize_window(window_def: &WindowDefinition, monitor: gdk::Rectangle) -> Option<gtk::Window> {
             let window = gtk::Window::new(gtk::WindowType::Toplevel);
             // Initialising a layer shell surface
@@ -54,55 +91,59 @@ mod platform_wayland {
                 WindowStacking::Overlay => gtk_layer_shell::set_layer(&window, gtk_layer_shell::Layer::Overlay),
             }
 
-            if let Some(namespace) = &window_def.backend_options.wayland.namespace {
-                gtk_layer_shell::set_namespace(&window, namespace);
-            }
-
             // Sets the keyboard interactivity
             gtk_layer_shell::set_keyboard_interactivity(&window, window_def.backend_options.wayland.focusable);
 
             if let Some(geometry) = window_def.geometry {
-                // Positioning surface
-                let mut top = false;
-                let mut left = false;
-                let mut right = false;
-                let mut bottom = false;
-
-                match geometry.anchor_point.x {
-                    AnchorAlignment::START => left = true,
-                    AnchorAlignment::CENTER => {}
-                    AnchorAlignment::END => right = true,
-                }
-                match geometry.anchor_point.y {
-                    AnchorAlignment::START => top = true,
-                    AnchorAlignment::CENTER => {}
-                    AnchorAlignment::END => bottom = true,
-                }
-
-                gtk_layer_shell::set_anchor(&window, gtk_layer_shell::Edge::Left, left);
-                gtk_layer_shell::set_anchor(&window, gtk_layer_shell::Edge::Right, right);
-                gtk_layer_shell::set_anchor(&window, gtk_layer_shell::Edge::Top, top);
-                gtk_layer_shell::set_anchor(&window, gtk_layer_shell::Edge::Bottom, bottom);
-
-                let xoffset = geometry.offset.x.pixels_relative_to(monitor.width());
-                let yoffset = geometry.offset.y.pixels_relative_to(monitor.height());
-
-                if left {
-                    gtk_layer_shell::set_margin(&window, gtk_layer_shell::Edge::Left, xoffset);
-                } else {
-                    gtk_layer_shell::set_margin(&window, gtk_layer_shell::Edge::Right, xoffset);
-                }
-                if bottom {
-                    gtk_layer_shell::set_margin(&window, gtk_layer_shell::Edge::Bottom, yoffset);
-                } else {
-                    gtk_layer_shell::set_margin(&window, gtk_layer_shell::Edge::Top, yoffset);
-                }
+                Self::apply_position(&window, geometry, monitor);
             }
             if window_def.backend_options.wayland.exclusive {
                 gtk_layer_shell::auto_exclusive_zone_enable(&window);
             }
             Some(window)
         }
+
+        fn set_namespace(window: &gtk::Window, namespace: &str) {
+            gtk_layer_shell::set_namespace(window, namespace);
+        }
+
+        fn apply_position(window: &gtk::Window, geometry: WindowGeometry, monitor: gdk::Rectangle) {
+            // Positioning surface
+            let mut top = false;
+            let mut left = false;
+            let mut right = false;
+            let mut bottom = false;
+
+            match geometry.anchor_point.x {
+                AnchorAlignment::START => left = true,
+                AnchorAlignment::CENTER => {}
+                AnchorAlignment::END => right = true,
+            }
+            match geometry.anchor_point.y {
+                AnchorAlignment::START => top = true,
+                AnchorAlignment::CENTER => {}
+                AnchorAlignment::END => bottom = true,
+            }
+
+            gtk_layer_shell::set_anchor(window, gtk_layer_shell::Edge::Left, left);
+            gtk_layer_shell::set_anchor(window, gtk_layer_shell::Edge::Right, right);
+            gtk_layer_shell::set_anchor(window, gtk_layer_shell::Edge::Top, top);
+            gtk_layer_shell::set_anchor(window, gtk_layer_shell::Edge::Bottom, bottom);
+
+            let xoffset = geometry.offset.x.pixels_relative_to(monitor.width());
+            let yoffset = geometry.offset.y.pixels_relative_to(monitor.height());
+
+            if left {
+                gtk_layer_shell::set_margin(window, gtk_layer_shell::Edge::Left, xoffset);
+            } else {
+                gtk_layer_shell::set_margin(window, gtk_layer_shell::Edge::Right, xoffset);
+            }
+            if bottom {
+                gtk_layer_shell::set_margin(window, gtk_layer_shell::Edge::Bottom, yoffset);
+            } else {
+                gtk_layer_shell::set_margin(window, gtk_layer_shell::Edge::Top, yoffset);
+            }
+        }
     }
 }
 
@@ -120,7 +161,7 @@ mod platform_x11 {
     };
     use yuck::config::{
         backend_window_options::{Side, X11WindowType},
-        window_definition::{WindowDefinition, WindowStacking},
+        window_definition::WindowDefinition,
     };
 
     use super::DisplayBackend;
@@ -135,8 +176,8 @@ mod platform_x11 {
             #[allow(deprecated)]
             window.set_wmclass(&wm_class_name, &wm_class_name);
             window.set_resizable(window_def.resizable);
-            window.set_keep_above(window_def.stacking == WindowStacking::Foreground);
-            window.set_keep_below(window_def.stacking == WindowStacking::Background);
+            window.set_keep_above(window_def.stacking.is_above());
+            window.set_keep_below(window_def.stacking.is_below());
             if window_def.backend_options.x11.sticky {
                 window.stick();
             } else {
@@ -144,6 +185,10 @@ mod platform_x11 {
             }
             Some(window)
         }
+
+        fn set_window_properties(window: &gtk::Window, monitor: gdk::Rectangle, window_def: &WindowDefinition) -> Result<()> {
+            set_xprops(window, monitor, window_def)
+        }
     }
 
     pub fn set_xprops(window: &gtk::Window, monitor: gdk::Rectangle, window_def: &WindowDefinition) -> Result<()> {
@@ -177,27 +222,20 @@ mod platform_x11 {
                 gdk_window.downcast_ref::<gdkx11::X11Window>().context("Failed to get x11 window for gtk window")?.xid() as u32;
             let strut_def = window_def.backend_options.x11.struts;
             let root_window_geometry = self.conn.get_geometry(self.root_window)?.reply()?;
-
-            let mon_end_x = (monitor_rect.x() + monitor_rect.width()) as u32 - 1u32;
-            let mon_end_y = (monitor_rect.y() + monitor_rect.height()) as u32 - 1u32;
-
-            let dist = match strut_def.side {
-                Side::Left | Side::Right => strut_def.dist.pixels_relative_to(monitor_rect.width()) as u32,
-                Side::Top | Side::Bottom => strut_def.dist.pixels_relative_to(monitor_rect.height()) as u32,
-            };
-
-            // don't question it,.....
-            // it's how the X gods want it to be.
-            // left, right, top, bottom, left_start_y, left_end_y, right_start_y, right_end_y, top_start_x, top_end_x, bottom_start_x, bottom_end_x
-            #[rustfmt::skip]
-            let strut_list: Vec<u8> = match strut_def.side {
-                Side::Left   => vec![dist + monitor_rect.x() as u32,  0,                                                     0,                             0,                                                      monitor_rect.y() as u32,  mon_end_y,  0,                      0,          0,                      0,          0,                      0],
-                Side::Right  => vec![0,                             root_window_geometry.width as u32 - mon_end_x + dist,  0,                             0,                                                      0,                      0,          monitor_rect.y() as u32,  mon_end_y,  0,                      0,          0,                      0],
-                Side::Top    => vec![0,                             0,                                                     dist + monitor_rect.y() as u32,  0,                                                      0,                      0,          0,                      0,          monitor_rect.x() as u32,  mon_end_x,  0,                      0],
-                Side::Bottom => vec![0,                             0,                                                     0,                             root_window_geometry.height as u32 - mon_end_y + dist,  0,                      0,          0,                      0,          0,                      0,          monitor_rect.x() as u32,  mon_end_x],
-                // This should never happen but if it does the window will be anchored on the
-                // right of the screen
-            }.iter().flat_map(|x| x.to_le_bytes().to_vec()).collect();
+            // gtk works with logical pixels, while struts are given in device pixels, which differ on scaled (HiDPI) setups.
+            let scale_factor = gdk_window.scale_factor();
+
+            let dist = strut_def.reserved_distance(window_def.geometry.as_ref(), monitor_rect.width(), monitor_rect.height());
+            let strut_list: Vec<u8> = get_strut_list(
+                strut_def.side,
+                dist * scale_factor as u32,
+                scale_to_device_pixels(monitor_rect, scale_factor),
+                root_window_geometry.width as u32,
+                root_window_geometry.height as u32,
+            )
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect();
 
             self.conn
                 .change_property(
@@ -245,6 +283,32 @@ mod platform_x11 {
         }
     }
 
+    /// Scale a rectangle given in logical pixels, as used by gtk, to device pixels.
+    fn scale_to_device_pixels(rect: gdk::Rectangle, scale_factor: i32) -> gdk::Rectangle {
+        let (x, y, width, height) = (rect.x(), rect.y(), rect.width(), rect.height());
+        gdk::Rectangle::new(x * scale_factor, y * scale_factor, width * scale_factor, height * scale_factor)
+    }
+
+    /// Get the values of the `_NET_WM_STRUT_PARTIAL` property, reserving `dist` pixels on the given side of the monitor.
+    /// All values are in device pixels.
+    #[rustfmt::skip]
+    fn get_strut_list(side: Side, dist: u32, monitor_rect: gdk::Rectangle, root_width: u32, root_height: u32) -> Vec<u32> {
+        let mon_end_x = (monitor_rect.x() + monitor_rect.width()) as u32 - 1u32;
+        let mon_end_y = (monitor_rect.y() + monitor_rect.height()) as u32 - 1u32;
+
+        // don't question it,.....
+        // it's how the X gods want it to be.
+        // left, right, top, bottom, left_start_y, left_end_y, right_start_y, right_end_y, top_start_x, top_end_x, bottom_start_x, bottom_end_x
+        match side {
+            Side::Left   => vec![dist + monitor_rect.x() as u32, 0,                             0,                              0,                              monitor_rect.y() as u32, mon_end_y, 0,                       0,         0,                       0,         0,                       0],
+            Side::Right  => vec![0,                              root_width - mon_end_x + dist, 0,                              0,                              0,                       0,         monitor_rect.y() as u32, mon_end_y, 0,                       0,         0,                       0],
+            Side::Top    => vec![0,                              0,                             dist + monitor_rect.y() as u32, 0,                              0,                       0,         0,                       0,         monitor_rect.x() as u32, mon_end_x, 0,                       0],
+            Side::Bottom => vec![0,                              0,                             0,                              root_height - mon_end_y + dist, 0,                       0,         0,                       0,         0,                       0,         monitor_rect.x() as u32, mon_end_x],
+            // This should never happen but if it does the window will be anchored on the
+            // right of the screen
+        }
+    }
+
     x11rb::atom_manager! {
         pub AtomCollection: AtomCollectionCookie {
             _NET_WM_WINDOW_TYPE,
@@ -271,4 +335,21 @@ mod platform_x11 {
             STRING,
         }
     }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_strut_list_with_scale_factor() {
+            // A 1920x1080 monitor, scaled by a factor of 2, to the right of another monitor of the same size
+            let monitor = scale_to_device_pixels(gdk::Rectangle::new(1920, 0, 1920, 1080), 2);
+            assert_eq!(monitor, gdk::Rectangle::new(3840, 0, 3840, 2160));
+
+            let top = get_strut_list(Side::Top, 30 * 2, monitor, 7680, 2160);
+            assert_eq!(top, vec![0, 0, 60, 0, 0, 0, 0, 0, 3840, 7679, 0, 0]);
+            let left = get_strut_list(Side::Left, 30 * 2, monitor, 7680, 2160);
+            assert_eq!(left, vec![3900, 0, 0, 0, 0, 2159, 0, 0, 0, 0, 0, 0]);
+        }
+    }
 }
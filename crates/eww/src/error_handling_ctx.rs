@@ -1,7 +1,10 @@
 //! Disgusting global state.
 //! I hate this, but [buffet](https://github.com/buffet) told me that this is what I should do for peak maintainability!
 
-use std::sync::{Arc, RwLock};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, RwLock},
+};
 
 use codespan_reporting::{
     diagnostic::Diagnostic,
@@ -12,7 +15,7 @@ use once_cell::sync::Lazy;
 use simplexpr::{dynval::ConversionError, eval::EvalError};
 use yuck::{config::validate::ValidationError, error::DiagError, format_diagnostic::ToDiagnostic};
 
-use crate::file_database::FileDatabase;
+use crate::{daemon_response::ErrorKind, file_database::FileDatabase};
 
 pub static FILE_DATABASE: Lazy<Arc<RwLock<FileDatabase>>> = Lazy::new(|| Arc::new(RwLock::new(FileDatabase::new())));
 
@@ -20,7 +23,64 @@ pub fn clear_files() {
     *FILE_DATABASE.write().unwrap() = FileDatabase::new();
 }
 
+/// Maximum amount of errors kept in [`ERROR_HISTORY`]. When full, the oldest errors get dropped.
+const MAX_ERROR_HISTORY_LEN: usize = 50;
+
+/// The most recent errors that occurred in the daemon, oldest first.
+static ERROR_HISTORY: Lazy<Mutex<VecDeque<ErrorHistoryEntry>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// An error that occurred in the daemon, see [`record_error`].
+#[derive(Debug, Clone)]
+pub struct ErrorHistoryEntry {
+    pub time: chrono::DateTime<chrono::Local>,
+    /// Name of the command that failed, or what the daemon was doing when the error occurred
+    pub source: String,
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl ErrorHistoryEntry {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "time": self.time.to_rfc3339(),
+            "source": self.source,
+            "kind": self.kind,
+            "message": self.message,
+        })
+    }
+}
+
+impl std::fmt::Display for ErrorHistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] {}", self.time.format("%Y-%m-%d %H:%M:%S"), self.source, self.message)
+    }
+}
+
+/// Add an error to the error history, dropping the oldest entry if the history is full.
+pub fn record_error(source: impl Into<String>, kind: ErrorKind, message: impl Into<String>) {
+    let mut history = ERROR_HISTORY.lock().unwrap();
+    if history.len() >= MAX_ERROR_HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(ErrorHistoryEntry { time: chrono::Local::now(), source: source.into(), kind, message: message.into() });
+}
+
+/// Get the most recent errors that occurred in the daemon, oldest first.
+pub fn error_history() -> Vec<ErrorHistoryEntry> {
+    ERROR_HISTORY.lock().unwrap().iter().cloned().collect()
+}
+
+/// Print an error, recording it in the error history as caused by `source`.
+pub fn print_error_from(source: &str, err: anyhow::Error) {
+    record_error(source, ErrorKind::of(&err), format!("{:#}", err));
+    log_error(err);
+}
+
 pub fn print_error(err: anyhow::Error) {
+    print_error_from("daemon", err)
+}
+
+fn log_error(err: anyhow::Error) {
     match anyhow_err_to_diagnostic(&err) {
         Some(diag) => match stringify_diagnostic(diag) {
             Ok(diag) => eprintln!("{}", diag),
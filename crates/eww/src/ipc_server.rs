@@ -1,6 +1,5 @@
-use crate::{app, opts};
+use crate::{app, daemon_response::DaemonResponse, opts};
 use anyhow::{Context, Result};
-use std::time::Duration;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     sync::mpsc::*,
@@ -32,23 +31,66 @@ async fn handle_connection(mut stream: tokio::net::UnixStream, evt_send: Unbound
     let action: opts::ActionWithServer = read_action_from_stream(&mut stream_read).await?;
 
     log::debug!("received command from IPC: {:?}", &action);
+    crate::command_recording::record(&action);
 
+    let response_timeout = action.response_timeout();
+    let is_streaming = action.is_streaming();
     let (command, maybe_response_recv) = action.into_daemon_command();
 
     evt_send.send(command)?;
 
-    if let Some(mut response_recv) = maybe_response_recv {
-        log::debug!("Waiting for response for IPC client");
-        if let Ok(Some(response)) = tokio::time::timeout(Duration::from_millis(100), response_recv.recv()).await {
-            let response = bincode::serialize(&response)?;
-            let result = &stream_write.write_all(&response).await;
-            crate::print_result_err!("sending text response to ipc client", &result);
+    match maybe_response_recv {
+        Some(mut response_recv) if is_streaming => {
+            // The client doesn't send anything after its request, so reading only returns once it disconnected.
+            let mut buf = [0u8; 1];
+            loop {
+                tokio::select! {
+                    response = response_recv.recv() => match response {
+                        Some(response) => match write_streamed_response(&mut stream_write, &response).await {
+                            Err(err) if is_disconnect(&err) => break,
+                            result => result?,
+                        },
+                        None => break,
+                    },
+                    _ = stream_read.read(&mut buf) => break,
+                }
+            }
+            // The client may already be gone, so shutting down the stream could fail. Dropping it closes it either way.
+            return Ok(());
         }
+        Some(mut response_recv) => {
+            log::debug!("Waiting for response for IPC client");
+            let response = match response_timeout {
+                Some(response_timeout) => tokio::time::timeout(response_timeout, response_recv.recv()).await.ok().flatten(),
+                None => response_recv.recv().await,
+            };
+            if let Some(response) = response {
+                let response = bincode::serialize(&response)?;
+                let result = &stream_write.write_all(&response).await;
+                crate::print_result_err!("sending text response to ipc client", &result);
+            }
+        }
+        None => {}
     }
     stream_write.shutdown().await?;
     Ok(())
 }
 
+/// Write a response of a streaming action, prefixed with its size such that the client can tell the responses apart.
+async fn write_streamed_response(
+    stream_write: &mut tokio::net::unix::WriteHalf<'_>,
+    response: &DaemonResponse,
+) -> std::io::Result<()> {
+    let response = bincode::serialize(response).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    stream_write.write_all(&(response.len() as u32).to_be_bytes()).await?;
+    stream_write.write_all(&response).await
+}
+
+/// Whether an error writing to a client means that the client disconnected, which ends streaming actions normally.
+fn is_disconnect(err: &std::io::Error) -> bool {
+    matches!(err.kind(), std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset)
+}
+
 /// Read a single message from a unix stream, and parses it into a `ActionWithServer`
 /// The format here requires the first 4 bytes to be the size of the rest of the message (in big-endian), followed by the rest of the message.
 async fn read_action_from_stream(stream_read: &'_ mut tokio::net::unix::ReadHalf<'_>) -> Result<opts::ActionWithServer> {
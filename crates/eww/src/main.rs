@@ -22,6 +22,7 @@ use crate::server::ForkResult;
 mod app;
 mod application_lifecycle;
 mod client;
+mod command_recording;
 mod config;
 mod daemon_response;
 mod display_backend;
@@ -31,11 +32,14 @@ mod geometry;
 mod ipc_server;
 mod opts;
 mod paths;
+mod persisted_vars;
+mod process_output;
 mod script_var_handler;
 mod server;
 mod state;
 mod util;
 mod widgets;
+mod window_arguments;
 
 fn main() {
     let eww_binary_name = std::env::args().next().unwrap();
@@ -89,6 +93,10 @@ fn run<B: DisplayBackend>(opts: opts::Opt, eww_binary_name: String, display_back
         .map(EwwPaths::from_config_dir)
         .unwrap_or_else(EwwPaths::default)
         .context("Failed to initialize eww paths")?;
+    let paths = match opts.socket_path {
+        Some(socket_path) => paths.with_ipc_socket_file(socket_path),
+        None => paths,
+    };
 
     let should_restart = match &opts.action {
         opts::Action::Daemon => opts.restart,
@@ -132,6 +140,33 @@ fn run<B: DisplayBackend>(opts: opts::Opt, eww_binary_name: String, display_back
             false
         }
 
+        opts::Action::WithServer(action) if action.is_streaming() => {
+            let socket_path = paths.get_ipc_socket_file();
+            let mut stream = attempt_connect(&socket_path, 5).context("Failed to connect to daemon")?;
+            client::do_streaming_server_call(&mut stream, &action, handle_daemon_response)?;
+            false
+        }
+
+        opts::Action::WithServer(action @ ActionWithServer::IsWindowOpen { .. }) => {
+            match handle_server_command(&paths, &action, 5)? {
+                Some(DaemonResponse::Success(is_open)) => {
+                    println!("{}", is_open);
+                    if is_open != "true" {
+                        std::process::exit(1);
+                    }
+                }
+                Some(DaemonResponse::Failure { message, .. }) => {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+                None => {
+                    eprintln!("The eww daemon did not respond in time");
+                    std::process::exit(2);
+                }
+            }
+            false
+        }
+
         // a running daemon is necessary for this command
         opts::Action::WithServer(action) => {
             // attempt to just send the command to a running daemon
@@ -140,6 +175,10 @@ fn run<B: DisplayBackend>(opts: opts::Opt, eww_binary_name: String, display_back
                     handle_daemon_response(response);
                     true
                 }
+                Ok(None) if action == ActionWithServer::Ping => {
+                    eprintln!("The eww daemon did not respond in time");
+                    std::process::exit(1);
+                }
                 Ok(None) => true,
 
                 Err(err) if action.can_start_daemon() && !opts.no_daemonize => {
@@ -195,8 +234,8 @@ fn handle_server_command(paths: &EwwPaths, action: &ActionWithServer, connect_at
 fn handle_daemon_response(res: DaemonResponse) {
     match res {
         DaemonResponse::Success(x) => println!("{}", x),
-        DaemonResponse::Failure(x) => {
-            eprintln!("{}", x);
+        DaemonResponse::Failure { message, .. } => {
+            eprintln!("{}", message);
             std::process::exit(1);
         }
     }
@@ -21,6 +21,7 @@ pub struct Opt {
     pub show_logs: bool,
     pub restart: bool,
     pub config_path: Option<std::path::PathBuf>,
+    pub socket_path: Option<std::path::PathBuf>,
     pub action: Action,
     pub no_daemonize: bool,
 }
@@ -41,6 +42,12 @@ struct RawOpt {
     #[arg(short, long, global = true)]
     config: Option<std::path::PathBuf>,
 
+    /// override path to the IPC socket used to communicate with the daemon.
+    /// Can also be set via the EWW_SOCKET environment variable.
+    /// Defaults to a file in $XDG_RUNTIME_DIR derived from the configuration directory.
+    #[arg(long = "socket", global = true)]
+    socket: Option<std::path::PathBuf>,
+
     /// Watch the log output after executing the command
     #[arg(long = "logs", global = true)]
     show_logs: bool,
@@ -75,11 +82,16 @@ pub enum ActionClientOnly {
     /// Print and watch the eww logs
     #[command(name = "logs")]
     Logs,
+
+    /// Check the configuration and stylesheet for errors, without affecting a running daemon.
+    #[command(name = "validate")]
+    Validate,
 }
 
 #[derive(Subcommand, Debug, Serialize, Deserialize, PartialEq)]
 pub enum ActionWithServer {
-    /// Ping the eww server, checking if it is reachable.
+    /// Ping the eww server, checking if it is reachable and responsive.
+    /// Exits with a non-zero status if the daemon does not respond in time.
     #[clap(name = "ping")]
     Ping,
 
@@ -91,6 +103,18 @@ pub enum ActionWithServer {
         mappings: Vec<(VarName, DynVal)>,
     },
 
+    /// Set the value of a variable, reporting an error if it doesn't exist.
+    /// With `--persist`, the value is also kept when the daemon restarts.
+    #[command(name = "set")]
+    SetVar {
+        name: String,
+        value: String,
+
+        /// Keep the value across restarts of the daemon. Only possible for variables defined via defvar.
+        #[arg(long)]
+        persist: bool,
+    },
+
     /// Open the GTK debugger
     #[command(name = "inspector", alias = "debugger")]
     OpenInspector,
@@ -101,13 +125,24 @@ pub enum ActionWithServer {
         /// Name of the window you want to open.
         window_name: String,
 
+        /// Id of this window instance, allowing the same window to be opened multiple times.
+        /// Defaults to the name of the window.
+        #[arg(long)]
+        id: Option<String>,
+
         /// The identifier of the monitor the window should open on
         #[arg(long)]
         screen: Option<MonitorIdentifier>,
 
+        /// Open an instance of the window on every monitor.
+        /// The instances get the id of the window, followed by the index of their monitor, i.e. `bar-0`.
+        #[arg(long, conflicts_with = "screen")]
+        all_monitors: bool,
+
         /// The position of the window, where it should open. (i.e.: 200x100)
-        #[arg(short, long)]
-        pos: Option<Coords>,
+        /// Use `cursor` to place the anchor point of the window at the mouse cursor.
+        #[arg(short, long, value_parser = parse_window_position)]
+        pos: Option<WindowPosition>,
 
         /// The size of the window to open (i.e.: 200x100)
         #[arg(short, long)]
@@ -117,9 +152,27 @@ pub enum ActionWithServer {
         #[arg(short, long)]
         anchor: Option<AnchorPoint>,
 
+        /// Close the window again after the given duration (i.e.: 5s).
+        /// Opening the window again restarts the timeout.
+        #[arg(long, value_parser = parse_duration)]
+        close_after: Option<std::time::Duration>,
+
+        /// Id of an open window to place this window relative to.
+        /// The anchor, position and size of the window are then resolved within that window instead of the monitor.
+        #[arg(long)]
+        relative_to: Option<String>,
+
         /// If the window is already open, close it instead
         #[arg(long = "toggle")]
         should_toggle: bool,
+
+        /// What to do if the window is already open
+        #[arg(long, value_enum, default_value_t = AlreadyOpenBehavior::Reopen)]
+        if_open: AlreadyOpenBehavior,
+
+        /// Value for an argument of the window, formatted like `name="value"`
+        #[arg(long = "arg", value_parser = parse_var_update_arg)]
+        args: Vec<(VarName, DynVal)>,
     },
 
     /// Open multiple windows at once.
@@ -131,20 +184,149 @@ pub enum ActionWithServer {
         /// If a window is already open, close it instead
         #[arg(long = "toggle")]
         should_toggle: bool,
+
+        /// What to do if a window is already open
+        #[arg(long, value_enum, default_value_t = AlreadyOpenBehavior::Reopen)]
+        if_open: AlreadyOpenBehavior,
+    },
+
+    /// Close the given windows if they are open, and open them otherwise.
+    /// This is a shorthand for `eww open-many --toggle`.
+    #[command(name = "toggle")]
+    ToggleWindows { windows: Vec<String> },
+
+    /// Move or resize an open window, without recreating it.
+    /// Like the options of `eww open`, the new geometry is remembered when the window is opened again.
+    #[command(name = "move")]
+    MoveWindow {
+        /// Id of the open window to move
+        window_id: String,
+
+        /// The new position of the window (i.e.: 200x100)
+        #[arg(short, long)]
+        pos: Option<Coords>,
+
+        /// The new size of the window (i.e.: 200x100)
+        #[arg(short, long)]
+        size: Option<Coords>,
+
+        /// The new sidepoint of the window, formatted like "top right"
+        #[arg(short, long)]
+        anchor: Option<AnchorPoint>,
+    },
+
+    /// Set the CSS classes of the root widget of an open window, replacing the ones previously set with this command.
+    /// Giving no classes removes all classes set with this command.
+    #[command(name = "set-class")]
+    SetWindowClass {
+        /// Id of the open window
+        window_id: String,
+
+        /// The CSS classes to set
+        classes: Vec<String>,
+    },
+
+    /// Print the geometry a window would have when opened with the given options, without opening it.
+    ///
+    /// The geometry is printed as WIDTHxHEIGHT+X+Y, in global coordinates.
+    #[command(name = "geometry")]
+    GetWindowGeometry {
+        /// Name of the window
+        window_name: String,
+
+        /// The identifier of the monitor the window would open on
+        #[arg(long)]
+        screen: Option<MonitorIdentifier>,
+
+        /// The position of the window (i.e.: 200x100)
+        #[arg(short, long)]
+        pos: Option<Coords>,
+
+        /// The size of the window (i.e.: 200x100)
+        #[arg(short, long)]
+        size: Option<Coords>,
+
+        /// Sidepoint of the window, formatted like "top right"
+        #[arg(short, long)]
+        anchor: Option<AnchorPoint>,
     },
 
     /// Close the given windows
     #[command(name = "close", alias = "c")]
     CloseWindows { windows: Vec<String> },
 
+    /// Switch to the configuration in a different directory, closing all currently open windows.
+    /// The IPC socket and log file of the daemon stay the same.
+    #[command(name = "switch-config")]
+    SwitchConfig {
+        /// Path to the configuration directory to switch to
+        #[arg(value_parser = parse_config_dir)]
+        config_dir: std::path::PathBuf,
+
+        /// Windows of the new configuration to open after switching
+        #[arg(long, num_args = 1..)]
+        open: Vec<String>,
+    },
+
+    /// Apply the size, position and stacking of all open windows again.
+    /// This is useful when the window manager moved or resized eww windows.
+    #[command(name = "reassert-geometry")]
+    ReassertGeometry,
+
+    /// Forget the geometry remembered for the given window instances, or for all windows if none are given.
+    /// When opened again, these windows use the geometry from the configuration.
+    #[command(name = "forget-geometry")]
+    ForgetGeometry {
+        /// Ids of the window instances
+        windows: Vec<String>,
+    },
+
+    /// Force the given windows to be drawn again, or all open windows if none are given.
+    /// This can help when a window shows outdated content, i.e. after changing the GTK theme.
+    #[command(name = "redraw")]
+    Redraw {
+        /// Ids of the windows to redraw
+        windows: Vec<String>,
+    },
+
+    /// Move the focus to the next open window that can be focused, ordered by the window ids
+    #[command(name = "focus")]
+    FocusWindow {
+        /// Focus the previous window instead of the next one
+        #[arg(long)]
+        previous: bool,
+    },
+
     /// Reload the configuration
     #[command(name = "reload", alias = "r")]
     Reload,
 
+    /// Reload only the stylesheet, leaving the open windows as they are
+    #[command(name = "reload-css")]
+    ReloadCss,
+
+    /// Reload only the configuration, leaving the stylesheet as it is
+    #[command(name = "reload-config")]
+    ReloadConfig,
+
     /// Kill the eww daemon
     #[command(name = "kill", alias = "k")]
     KillServer,
 
+    /// Pause all script-vars (defpoll and deflisten), keeping the current values of the variables.
+    /// The variables stay paused until resumed, even when the configuration gets reloaded.
+    #[command(name = "pause-vars")]
+    PauseScriptVars,
+
+    /// Resume all script-vars that were paused by `eww pause-vars`
+    #[command(name = "resume-vars")]
+    ResumeScriptVars,
+
+    /// Restart a single script-var (defpoll or deflisten), i.e. when its listen-script crashed.
+    /// Script-vars that aren't running, because no open window uses them or because of their `:run-while`, stay stopped.
+    #[command(name = "restart-var")]
+    RestartScriptVar { name: String },
+
     /// Close all windows, without killing the daemon
     #[command(name = "close-all", alias = "ca")]
     CloseAll,
@@ -155,15 +337,113 @@ pub enum ActionWithServer {
         /// Shows all variables, including not currently used ones
         #[arg(short, long)]
         all: bool,
+
+        /// Print the variables as a JSON object mapping their names to their values
+        #[arg(long)]
+        json: bool,
     },
 
     /// Get the value of a variable if defined
     #[command(name = "get")]
     GetVar { name: String },
 
+    /// Print the current value of an attribute of a widget in an open window.
+    /// The widget needs to be given an id, using the `:id` attribute.
+    #[command(name = "get-attr")]
+    GetWidgetAttribute {
+        /// Id of the open window the widget is in
+        window_id: String,
+        /// Id of the widget, as given in its `:id` attribute
+        widget_id: String,
+        /// Name of the attribute, i.e. `text`
+        attribute: String,
+    },
+
+    /// Print the widgets of an open window as a tree, listing the type, CSS classes and allocated size of each widget.
+    #[command(name = "inspect-window")]
+    InspectWindow {
+        /// Id of the open window
+        window_id: String,
+    },
+
+    /// Save a screenshot of the contents of an open window as a PNG image.
+    #[command(name = "screenshot")]
+    Screenshot {
+        /// Id of the open window
+        window_id: String,
+        /// File to write the image to. Existing contents are replaced.
+        #[arg(value_parser = parse_absolute_path)]
+        path: std::path::PathBuf,
+    },
+
+    /// Print the index and name of the monitor an open window is currently on.
+    /// This is the monitor the window actually is on, which may differ from the configured one if the window was moved.
+    #[command(name = "window-monitor")]
+    GetWindowMonitor {
+        /// Id of the open window
+        window_id: String,
+    },
+
+    /// Wait until a variable satisfies a condition, then print its value.
+    ///
+    /// The condition is a simplexpr expression that gets re-evaluated whenever the variable or any variable used in it changes,
+    /// i.e.: `eww wait loading "loading == false"`
+    #[command(name = "wait")]
+    WaitForVar {
+        /// Name of the variable to wait for
+        name: String,
+
+        /// Expression that needs to evaluate to true
+        predicate: String,
+
+        /// Give up after the given duration (i.e.: 10s)
+        #[arg(short, long, value_parser = parse_duration)]
+        timeout: Option<std::time::Duration>,
+    },
+
+    /// Print the values of the given variables, and print them again whenever they change, until interrupted.
+    /// Each line has the shape `name: value`.
+    #[command(name = "listen")]
+    Subscribe {
+        /// Names of the variables to listen to
+        #[arg(required = true)]
+        vars: Vec<String>,
+    },
+
     /// Print the names of all configured windows. Windows with a * in front of them are currently opened.
     #[command(name = "windows")]
-    ShowWindows,
+    ShowWindows {
+        /// Print the windows as a JSON array of objects with `name` and `open` fields
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print whether a window is currently open, exiting with status 0 if it is and 1 if it isn't.
+    /// If no such window exists, this exits with status 2.
+    #[command(name = "is-open")]
+    IsWindowOpen {
+        /// Id of the window instance. This is the name of the window, unless a different id was given when opening it.
+        window_id: String,
+    },
+
+    /// Print the id, window name, monitor index and current geometry of every open window.
+    /// The geometry is printed as WIDTHxHEIGHT+X+Y, in global coordinates, as reported by the window system.
+    /// On Wayland, the position of windows is usually not known to eww.
+    #[command(name = "open-windows")]
+    ShowOpenWindows {
+        /// Print the windows as a JSON array of objects
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print the global variables used by the open windows, and which windows use them.
+    /// Script-vars are marked with a *, as these are the variables that have a running script.
+    #[command(name = "active-vars")]
+    ShowActiveVars {
+        /// Print the variables as a JSON array of objects with `name`, `script_var` and `windows` fields
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Print out the widget structure as seen by eww.
     ///
@@ -172,9 +452,91 @@ pub enum ActionWithServer {
     #[command(name = "debug")]
     ShowDebug,
 
+    /// Print the configuration the daemon currently uses as JSON, as it was parsed.
+    #[command(name = "dump-config")]
+    DumpConfig,
+
+    /// Print the builtin widgets and the properties they support.
+    #[command(name = "builtin-widgets")]
+    ShowBuiltinWidgets,
+
+    /// Print the names of all widgets defined in the configuration, together with the arguments they take.
+    /// Optional arguments are marked with a ? in front of them.
+    #[command(name = "widgets")]
+    ShowWidgets {
+        /// Print the widgets as a JSON array of objects with `name` and `args` fields
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Print out the scope graph structure in graphviz dot format.
     #[command(name = "graph")]
     ShowGraph,
+
+    /// Print the recent output of processes started by eww, such as script-vars and widget commands.
+    #[command(name = "process-output")]
+    ShowProcessOutput {
+        /// Only show output of sources containing this string, i.e. the name of a variable
+        #[arg(short, long)]
+        source: Option<String>,
+    },
+
+    /// Print a JSON description of everything the daemon is currently doing,
+    /// including open windows, running script-vars, widget listeners and clients waiting for variables.
+    #[command(name = "runtime")]
+    DescribeRuntime,
+
+    /// Print the most recent errors that occurred in the daemon, together with the command that caused them.
+    /// This includes failed commands as well as errors that were only logged, i.e. of script-vars.
+    #[command(name = "errors")]
+    ShowErrors {
+        /// Print the errors as a JSON array of objects with `time`, `source`, `kind` and `message` fields
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Start recording the commands the daemon receives into a file, such that they can be replayed via `eww replay`.
+    #[command(name = "record")]
+    StartRecording {
+        /// File to write the recording to. Existing contents are replaced.
+        #[arg(value_parser = parse_absolute_path)]
+        path: std::path::PathBuf,
+    },
+
+    /// Stop the active recording.
+    #[command(name = "stop-recording")]
+    StopRecording,
+
+    /// Replay the commands of a recording created via `eww record`, in order.
+    #[command(name = "replay")]
+    Replay {
+        /// The recording file to replay
+        #[arg(value_parser = parse_absolute_path)]
+        path: std::path::PathBuf,
+
+        /// Keep the original time between the commands, instead of replaying them immediately one after another
+        #[arg(long)]
+        keep_timing: bool,
+    },
+}
+
+/// What to do when opening a window that is already open.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlreadyOpenBehavior {
+    /// Close the window and open it again
+    Reopen,
+    /// Leave the window as it is
+    Ignore,
+    /// Leave the window as it is, but raise and focus it
+    Raise,
+}
+
+/// Position to open a window at, as given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WindowPosition {
+    Coords(Coords),
+    /// The position of the mouse cursor at the time the window is opened
+    Cursor,
 }
 
 impl Opt {
@@ -186,8 +548,8 @@ impl Opt {
 
 impl From<RawOpt> for Opt {
     fn from(other: RawOpt) -> Self {
-        let RawOpt { log_debug, force_wayland, config, show_logs, no_daemonize, restart, action } = other;
-        Opt { log_debug, force_wayland, show_logs, restart, config_path: config, action, no_daemonize }
+        let RawOpt { log_debug, force_wayland, config, socket, show_logs, no_daemonize, restart, action } = other;
+        Opt { log_debug, force_wayland, show_logs, restart, config_path: config, socket_path: socket, action, no_daemonize }
     }
 }
 
@@ -198,9 +560,50 @@ fn parse_var_update_arg(s: &str) -> Result<(VarName, DynVal)> {
     Ok((name.into(), DynVal::from_string(value.to_owned())))
 }
 
+fn parse_window_position(s: &str) -> Result<WindowPosition> {
+    if s == "cursor" {
+        Ok(WindowPosition::Cursor)
+    } else {
+        Ok(WindowPosition::Coords(s.parse()?))
+    }
+}
+
+fn parse_config_dir(s: &str) -> Result<std::path::PathBuf> {
+    std::fs::canonicalize(s).with_context(|| format!("Configuration directory {} does not exist", s))
+}
+
+/// Resolve a path relative to the working directory of the client, as the daemon runs in the configuration directory.
+/// Unlike [`parse_config_dir`], the path doesn't need to exist yet.
+fn parse_absolute_path(s: &str) -> Result<std::path::PathBuf> {
+    Ok(std::env::current_dir().context("Failed to get the current directory")?.join(s))
+}
+
+fn parse_duration(s: &str) -> Result<std::time::Duration> {
+    Ok(DynVal::from_string(s.to_owned()).as_duration()?)
+}
+
 impl ActionWithServer {
     pub fn can_start_daemon(&self) -> bool {
-        matches!(self, ActionWithServer::OpenWindow { .. } | ActionWithServer::OpenMany { .. })
+        matches!(
+            self,
+            ActionWithServer::OpenWindow { .. } | ActionWithServer::OpenMany { .. } | ActionWithServer::ToggleWindows { .. }
+        )
+    }
+
+    /// How long to wait for the daemon to respond to this action. `None` means waiting indefinitely.
+    pub fn response_timeout(&self) -> Option<std::time::Duration> {
+        match self {
+            ActionWithServer::WaitForVar { timeout, .. } => {
+                timeout.map(|timeout| timeout + std::time::Duration::from_millis(100))
+            }
+            ActionWithServer::Replay { .. } | ActionWithServer::Subscribe { .. } => None,
+            _ => Some(std::time::Duration::from_millis(100)),
+        }
+    }
+
+    /// Whether the daemon keeps sending responses to this action, rather than responding once.
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, ActionWithServer::Subscribe { .. })
     }
 
     pub fn into_daemon_command(self) -> (app::DaemonCommand, Option<daemon_response::DaemonResponseReceiver>) {
@@ -210,47 +613,187 @@ impl ActionWithServer {
 
             ActionWithServer::KillServer => app::DaemonCommand::KillServer,
             ActionWithServer::CloseAll => app::DaemonCommand::CloseAll,
-            ActionWithServer::Ping => {
-                let (send, recv) = tokio::sync::mpsc::unbounded_channel();
-                let _ = send.send(DaemonResponse::Success("pong".to_owned()));
-                return (app::DaemonCommand::NoOp, Some(recv));
+            ActionWithServer::PauseScriptVars => app::DaemonCommand::PauseScriptVars,
+            ActionWithServer::ResumeScriptVars => app::DaemonCommand::ResumeScriptVars,
+            ActionWithServer::Ping => return with_response_channel(app::DaemonCommand::Ping),
+            ActionWithServer::OpenMany { windows, should_toggle, if_open } => {
+                return with_response_channel(|sender| app::DaemonCommand::OpenMany { windows, should_toggle, if_open, sender });
             }
-            ActionWithServer::OpenMany { windows, should_toggle } => {
-                return with_response_channel(|sender| app::DaemonCommand::OpenMany { windows, should_toggle, sender });
+            ActionWithServer::ToggleWindows { windows } => {
+                return with_response_channel(|sender| app::DaemonCommand::OpenMany {
+                    windows,
+                    should_toggle: true,
+                    if_open: AlreadyOpenBehavior::Reopen,
+                    sender,
+                });
             }
-            ActionWithServer::OpenWindow { window_name, pos, size, screen, anchor, should_toggle } => {
+            ActionWithServer::OpenWindow {
+                window_name,
+                id,
+                pos,
+                size,
+                screen,
+                all_monitors,
+                anchor,
+                close_after,
+                relative_to,
+                should_toggle,
+                if_open,
+                args,
+            } => {
                 return with_response_channel(|sender| app::DaemonCommand::OpenWindow {
                     window_name,
+                    instance_id: id,
                     pos,
                     size,
                     anchor,
+                    close_after,
+                    relative_to,
                     screen,
+                    all_monitors,
                     should_toggle,
+                    if_open,
+                    args,
+                    sender,
+                })
+            }
+            ActionWithServer::GetWindowGeometry { window_name, screen, pos, size, anchor } => {
+                return with_response_channel(|sender| app::DaemonCommand::GetWindowGeometry {
+                    window_name,
+                    pos,
+                    size,
+                    anchor,
+                    screen,
+                    sender,
+                })
+            }
+            ActionWithServer::MoveWindow { window_id, pos, size, anchor } => {
+                return with_response_channel(|sender| app::DaemonCommand::MoveWindow {
+                    instance_id: window_id,
+                    pos,
+                    size,
+                    anchor,
+                    sender,
+                })
+            }
+            ActionWithServer::SetWindowClass { window_id, classes } => {
+                return with_response_channel(|sender| app::DaemonCommand::SetWindowClass {
+                    instance_id: window_id,
+                    classes,
                     sender,
                 })
             }
             ActionWithServer::CloseWindows { windows } => {
                 return with_response_channel(|sender| app::DaemonCommand::CloseWindows { windows, sender });
             }
+            ActionWithServer::SwitchConfig { config_dir, open } => {
+                return with_response_channel(|sender| app::DaemonCommand::SwitchConfig { config_dir, windows: open, sender })
+            }
+            ActionWithServer::ReassertGeometry => return with_response_channel(app::DaemonCommand::ReassertGeometry),
+            ActionWithServer::ForgetGeometry { windows } => {
+                return with_response_channel(|sender| app::DaemonCommand::ForgetGeometry { windows, sender })
+            }
+            ActionWithServer::Redraw { windows } => {
+                return with_response_channel(|sender| app::DaemonCommand::Redraw { windows, sender })
+            }
+            ActionWithServer::FocusWindow { previous } => {
+                return with_response_channel(|sender| app::DaemonCommand::FocusWindow { previous, sender })
+            }
             ActionWithServer::Reload => return with_response_channel(app::DaemonCommand::ReloadConfigAndCss),
-            ActionWithServer::ShowWindows => return with_response_channel(app::DaemonCommand::PrintWindows),
-            ActionWithServer::ShowState { all } => {
-                return with_response_channel(|sender| app::DaemonCommand::PrintState { all, sender })
+            ActionWithServer::ReloadCss => return with_response_channel(app::DaemonCommand::ReloadCss),
+            ActionWithServer::ReloadConfig => return with_response_channel(app::DaemonCommand::ReloadConfig),
+            ActionWithServer::ShowWindows { json } => {
+                return with_response_channel(|sender| app::DaemonCommand::PrintWindows { json, sender })
+            }
+            ActionWithServer::IsWindowOpen { window_id } => {
+                return with_response_channel(|sender| app::DaemonCommand::IsWindowOpen { window_id, sender })
+            }
+            ActionWithServer::ShowOpenWindows { json } => {
+                return with_response_channel(|sender| app::DaemonCommand::ListWindowsWithGeometry { json, sender })
+            }
+            ActionWithServer::ShowActiveVars { json } => {
+                return with_response_channel(|sender| app::DaemonCommand::PrintActiveVars { json, sender })
+            }
+            ActionWithServer::ShowState { all, json } => {
+                return with_response_channel(|sender| app::DaemonCommand::PrintState { all, json, sender })
             }
             ActionWithServer::GetVar { name } => {
                 return with_response_channel(|sender| app::DaemonCommand::GetVar { name, sender })
             }
+            ActionWithServer::SetVar { name, value, persist } => {
+                return with_response_channel(|sender| app::DaemonCommand::SetVar {
+                    name: name.into(),
+                    value: DynVal::from_string(value),
+                    persist,
+                    sender,
+                })
+            }
+            ActionWithServer::RestartScriptVar { name } => {
+                return with_response_channel(|sender| app::DaemonCommand::RestartScriptVar { name: name.into(), sender })
+            }
+            ActionWithServer::GetWidgetAttribute { window_id, widget_id, attribute } => {
+                return with_response_channel(|sender| app::DaemonCommand::GetWidgetAttribute {
+                    window_id,
+                    widget_id,
+                    attribute,
+                    sender,
+                })
+            }
+            ActionWithServer::GetWindowMonitor { window_id } => {
+                return with_response_channel(|sender| app::DaemonCommand::GetWindowMonitor { window_id, sender })
+            }
+            ActionWithServer::Screenshot { window_id, path } => {
+                return with_response_channel(|sender| app::DaemonCommand::Screenshot { window_id, path, sender })
+            }
+            ActionWithServer::InspectWindow { window_id } => {
+                return with_response_channel(|sender| app::DaemonCommand::InspectWindow { window_id, sender })
+            }
+            ActionWithServer::Subscribe { vars } => {
+                return with_response_channel(|sender| app::DaemonCommand::Subscribe {
+                    vars: vars.into_iter().map(VarName::from).collect(),
+                    sender,
+                })
+            }
+            ActionWithServer::WaitForVar { name, predicate, timeout } => {
+                return with_response_channel(|sender| app::DaemonCommand::WaitForVar {
+                    name: name.into(),
+                    predicate,
+                    timeout,
+                    sender,
+                })
+            }
             ActionWithServer::ShowDebug => return with_response_channel(app::DaemonCommand::PrintDebug),
+            ActionWithServer::DumpConfig => return with_response_channel(app::DaemonCommand::DumpConfig),
             ActionWithServer::ShowGraph => return with_response_channel(app::DaemonCommand::PrintGraph),
+            ActionWithServer::ShowBuiltinWidgets => return with_response_channel(app::DaemonCommand::PrintBuiltinWidgets),
+            ActionWithServer::ShowWidgets { json } => {
+                return with_response_channel(|sender| app::DaemonCommand::PrintWidgets { json, sender })
+            }
+            ActionWithServer::ShowProcessOutput { source } => {
+                return with_response_channel(|sender| app::DaemonCommand::PrintProcessOutput { source, sender })
+            }
+            ActionWithServer::DescribeRuntime => return with_response_channel(app::DaemonCommand::DescribeRuntime),
+            ActionWithServer::ShowErrors { json } => {
+                return with_response_channel(|sender| app::DaemonCommand::PrintErrors { json, sender })
+            }
+            ActionWithServer::StartRecording { path } => {
+                return with_response_channel(|sender| app::DaemonCommand::StartRecording { path, sender })
+            }
+            ActionWithServer::StopRecording => return with_response_channel(app::DaemonCommand::StopRecording),
+            ActionWithServer::Replay { path, keep_timing } => {
+                return with_response_channel(|sender| app::DaemonCommand::Replay { path, keep_timing, sender })
+            }
         };
         (command, None)
     }
 }
 
-fn with_response_channel<O, F>(f: F) -> (O, Option<tokio::sync::mpsc::UnboundedReceiver<DaemonResponse>>)
+fn with_response_channel<F>(f: F) -> (app::DaemonCommand, Option<tokio::sync::mpsc::UnboundedReceiver<DaemonResponse>>)
 where
-    F: FnOnce(DaemonResponseSender) -> O,
+    F: FnOnce(DaemonResponseSender) -> app::DaemonCommand,
 {
     let (sender, recv) = daemon_response::create_pair();
-    (f(sender), Some(recv))
+    let command = f(sender.clone());
+    sender.set_command_name(app::get_command_name(&command));
+    (command, Some(recv))
 }
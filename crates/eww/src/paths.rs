@@ -6,26 +6,22 @@ use std::{
 
 use anyhow::{bail, Result};
 
+/// Environment variable that can be used to override the path of the IPC socket.
+pub const IPC_SOCKET_ENV_VAR: &str = "EWW_SOCKET";
+
 /// Stores references to all the paths relevant to eww, and abstracts access to these files and directories
 #[derive(Debug, Clone)]
 pub struct EwwPaths {
     pub log_file: PathBuf,
     pub ipc_socket_file: PathBuf,
+    /// File containing the values of the variables persisted via `eww set --persist`
+    pub persisted_vars_file: PathBuf,
     pub config_dir: PathBuf,
 }
 
 impl EwwPaths {
     pub fn from_config_dir<P: AsRef<Path>>(config_dir: P) -> Result<Self> {
-        let config_dir = config_dir.as_ref();
-        if config_dir.is_file() {
-            bail!("Please provide the path to the config directory, not a file within it")
-        }
-
-        if !config_dir.exists() {
-            bail!("Configuration directory {} does not exist", config_dir.display());
-        }
-
-        let config_dir = config_dir.canonicalize()?;
+        let config_dir = canonicalize_config_dir(config_dir.as_ref())?;
 
         let mut hasher = DefaultHasher::new();
         format!("{}", config_dir.display()).hash(&mut hasher);
@@ -33,15 +29,14 @@ impl EwwPaths {
         // the absolute path to the socket stays under the 108 bytes limit. (see #387, man 7 unix)
         let daemon_id = format!("{:x}", hasher.finish());
 
-        let ipc_socket_file = std::env::var("XDG_RUNTIME_DIR")
-            .map(std::path::PathBuf::from)
-            .unwrap_or_else(|_| std::path::PathBuf::from("/tmp"))
-            .join(format!("eww-server_{}", daemon_id));
-
-        // 100 as the limit isn't quite 108 everywhere (i.e 104 on BSD or mac)
-        if format!("{}", ipc_socket_file.display()).len() > 100 {
-            log::warn!("The IPC socket file's absolute path exceeds 100 bytes, the socket may fail to create.");
-        }
+        let ipc_socket_file = match std::env::var(IPC_SOCKET_ENV_VAR) {
+            Ok(path) if !path.is_empty() => PathBuf::from(path),
+            _ => std::env::var("XDG_RUNTIME_DIR")
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|_| std::path::PathBuf::from("/tmp"))
+                .join(format!("eww-server_{}", daemon_id)),
+        };
+        warn_if_socket_path_too_long(&ipc_socket_file);
 
         Ok(EwwPaths {
             config_dir,
@@ -50,9 +45,18 @@ impl EwwPaths {
                 .unwrap_or_else(|_| PathBuf::from(std::env::var("HOME").unwrap()).join(".cache"))
                 .join(format!("eww_{}.log", daemon_id)),
             ipc_socket_file,
+            persisted_vars_file: std::env::var("XDG_STATE_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from(std::env::var("HOME").unwrap()).join(".local/state"))
+                .join(format!("eww_{}_vars.json", daemon_id)),
         })
     }
 
+    /// Use a different configuration directory, keeping the IPC socket and log file of the current paths.
+    pub fn with_config_dir(self, config_dir: &Path) -> Result<Self> {
+        Ok(EwwPaths { config_dir: canonicalize_config_dir(config_dir)?, ..self })
+    }
+
     pub fn default() -> Result<Self> {
         let config_dir = std::env::var("XDG_CONFIG_HOME")
             .map(PathBuf::from)
@@ -62,6 +66,12 @@ impl EwwPaths {
         Self::from_config_dir(config_dir)
     }
 
+    /// Use the given path for the IPC socket, instead of the one derived from the config directory.
+    pub fn with_ipc_socket_file(self, ipc_socket_file: PathBuf) -> Self {
+        warn_if_socket_path_too_long(&ipc_socket_file);
+        EwwPaths { ipc_socket_file, ..self }
+    }
+
     pub fn get_log_file(&self) -> &Path {
         self.log_file.as_path()
     }
@@ -70,6 +80,10 @@ impl EwwPaths {
         self.ipc_socket_file.as_path()
     }
 
+    pub fn get_persisted_vars_file(&self) -> &Path {
+        self.persisted_vars_file.as_path()
+    }
+
     pub fn get_config_dir(&self) -> &Path {
         self.config_dir.as_path()
     }
@@ -79,6 +93,25 @@ impl EwwPaths {
     }
 }
 
+fn canonicalize_config_dir(config_dir: &Path) -> Result<PathBuf> {
+    if config_dir.is_file() {
+        bail!("Please provide the path to the config directory, not a file within it")
+    }
+
+    if !config_dir.exists() {
+        bail!("Configuration directory {} does not exist", config_dir.display());
+    }
+
+    Ok(config_dir.canonicalize()?)
+}
+
+fn warn_if_socket_path_too_long(ipc_socket_file: &Path) {
+    // 100 as the limit isn't quite 108 everywhere (i.e 104 on BSD or mac)
+    if format!("{}", ipc_socket_file.display()).len() > 100 {
+        log::warn!("The IPC socket file's absolute path exceeds 100 bytes, the socket may fail to create.");
+    }
+}
+
 impl std::fmt::Display for EwwPaths {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
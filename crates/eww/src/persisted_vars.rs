@@ -0,0 +1,65 @@
+//! Values of variables set via `eww set --persist`, which are kept across restarts of the daemon.
+//!
+//! The values are stored as a JSON object mapping the names of the variables to their values,
+//! and replace the initial values of the corresponding `defvar`s whenever the state is generated from the configuration.
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, Result};
+use eww_shared_util::VarName;
+use simplexpr::dynval::DynVal;
+
+use crate::{config::EwwConfig, error_handling_ctx};
+
+/// Read all persisted values. A missing file means that no values have been persisted yet.
+pub fn read(path: &Path) -> Result<HashMap<VarName, String>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid persisted variables in {}", path.display()))
+}
+
+/// Read the persisted values of the `defvar`s of the given configuration.
+/// Values of variables that are no longer defined, or are now defined as script-vars or derived variables, are ignored.
+/// Errors are only logged, as the configuration can still be used with its initial values.
+pub fn read_for_config(path: &Path, config: &EwwConfig) -> HashMap<VarName, DynVal> {
+    match read(path) {
+        Ok(values) => values
+            .into_iter()
+            .filter(|(name, _)| config.get_initial_variables().contains_key(name))
+            .map(|(name, value)| (name, DynVal::from_string(value)))
+            .collect(),
+        Err(err) => {
+            error_handling_ctx::print_error(err);
+            HashMap::new()
+        }
+    }
+}
+
+/// Persist the value of a variable, keeping the values persisted for all other variables.
+pub fn persist(path: &Path, name: &VarName, value: &DynVal) -> Result<()> {
+    let mut values = read(path)?;
+    values.insert(name.clone(), value.0.clone());
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(&values)?;
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_persist_keeps_other_values() {
+        let path = std::env::temp_dir().join(format!("eww-test-persisted-vars-{}.json", std::process::id()));
+        persist(&path, &VarName::from("foo"), &DynVal::from_string("1".to_string())).unwrap();
+        persist(&path, &VarName::from("bar"), &DynVal::from_string("2".to_string())).unwrap();
+        persist(&path, &VarName::from("foo"), &DynVal::from_string("3".to_string())).unwrap();
+        let values = read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(values, maplit::hashmap! { VarName::from("foo") => "3".to_string(), VarName::from("bar") => "2".to_string() });
+    }
+}
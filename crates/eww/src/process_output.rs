@@ -0,0 +1,122 @@
+//! Central place for the output of processes spawned by eww, such as script-vars and widget commands.
+//!
+//! Every line is logged with a prefix naming its source, and the most recent lines are kept in a bounded buffer,
+//! which can be inspected via `eww process-output`.
+
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+
+/// Maximum amount of lines kept in the buffer. When full, the oldest lines get dropped.
+const MAX_BUFFERED_LINES: usize = 1000;
+
+/// Maximum amount of threads forwarding the output of widget commands at the same time.
+/// A command that starts a process in the background keeps its output open, and thereby its thread alive,
+/// so once this many are running, further commands don't get their output forwarded.
+const MAX_COMMAND_FORWARDERS: usize = 16;
+
+static ACTIVE_COMMAND_FORWARDERS: AtomicUsize = AtomicUsize::new(0);
+
+static PROCESS_OUTPUT: Lazy<Mutex<VecDeque<OutputLine>>> = Lazy::new(|| Mutex::new(VecDeque::with_capacity(MAX_BUFFERED_LINES)));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum OutputStream {
+    #[display(fmt = "stdout")]
+    Stdout,
+    #[display(fmt = "stderr")]
+    Stderr,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+    pub time: chrono::DateTime<chrono::Local>,
+    pub source: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+impl std::fmt::Display for OutputLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] ({}) {}", self.time.format("%H:%M:%S"), self.source, self.stream, self.line)
+    }
+}
+
+/// Log a line of output of a process, and add it to the buffer.
+pub fn record(source: &str, stream: OutputStream, line: &str) {
+    match stream {
+        OutputStream::Stdout => log::info!("[{}] {}", source, line),
+        OutputStream::Stderr => log::warn!("[{}] (stderr) {}", source, line),
+    }
+    let mut output = PROCESS_OUTPUT.lock().unwrap();
+    if output.len() >= MAX_BUFFERED_LINES {
+        output.pop_front();
+    }
+    output.push_back(OutputLine { time: chrono::Local::now(), source: source.to_string(), stream, line: line.to_string() });
+}
+
+/// Record every line of some text that a process has output.
+pub fn record_all(source: &str, stream: OutputStream, text: &str) {
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        record(source, stream, line);
+    }
+}
+
+/// One of the limited [`MAX_COMMAND_FORWARDERS`] threads, which is released again when dropped.
+pub struct CommandForwarderSlot(());
+
+impl Drop for CommandForwarderSlot {
+    fn drop(&mut self) {
+        ACTIVE_COMMAND_FORWARDERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Reserve a thread for forwarding the output of a widget command, if not all of them are in use.
+pub fn reserve_command_forwarder() -> Option<CommandForwarderSlot> {
+    ACTIVE_COMMAND_FORWARDERS
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| (active < MAX_COMMAND_FORWARDERS).then(|| active + 1))
+        .ok()
+        .map(|_| CommandForwarderSlot(()))
+}
+
+/// Record the lines read from a stream of a child process, until the stream is closed.
+/// This happens in a separate thread, to not block while the process is running, which keeps the given slot reserved.
+pub fn forward_lines(source: String, stream: OutputStream, reader: impl Read + Send + 'static, slot: CommandForwarderSlot) {
+    let result = std::thread::Builder::new().name("process-output-forwarder".to_string()).spawn(move || {
+        let _slot = slot;
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(line) => record(&source, stream, &line),
+                Err(_) => break,
+            }
+        }
+    });
+    crate::print_result_err!("starting thread to forward process output", result);
+}
+
+/// Format the buffered output, optionally only including lines of sources containing the given filter string.
+pub fn format_buffered_output(source_filter: Option<&str>) -> String {
+    let output = PROCESS_OUTPUT.lock().unwrap();
+    output.iter().filter(|line| source_filter.map_or(true, |filter| line.source.contains(filter))).join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_command_forwarders_are_limited() {
+        let slots = (0..MAX_COMMAND_FORWARDERS).map(|_| reserve_command_forwarder()).collect::<Option<Vec<_>>>();
+        assert!(slots.is_some());
+        assert!(reserve_command_forwarder().is_none());
+        drop(slots);
+        assert!(reserve_command_forwarder().is_some());
+    }
+}
@@ -1,8 +1,9 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use crate::{
     app,
     config::{create_script_var_failed_warn, script_var},
+    process_output::{self, OutputStream},
 };
 use anyhow::{anyhow, Result};
 use app::DaemonCommand;
@@ -37,6 +38,8 @@ pub fn init(evt_send: UnboundedSender<DaemonCommand>) -> ScriptVarHandlerHandle
                     let mut handler = ScriptVarHandler {
                         listen_handler: ListenVarHandler::new(evt_send.clone())?,
                         poll_handler: PollVarHandler::new(evt_send)?,
+                        running_vars: HashMap::new(),
+                        paused: false,
                     };
                     crate::loop_select_exiting! {
                         Some(msg) = msg_recv.recv() => match msg {
@@ -46,10 +49,22 @@ pub fn init(evt_send: UnboundedSender<DaemonCommand>) -> ScriptVarHandlerHandle
                             ScriptVarHandlerMsg::Stop(name) => {
                                 handler.stop_for_variable(&name).await?;
                             }
+                            ScriptVarHandlerMsg::Restart(name) => {
+                                handler.restart(&name).await?;
+                            }
+                            ScriptVarHandlerMsg::Reconcile(script_vars) => {
+                                handler.reconcile(script_vars).await?;
+                            }
                             ScriptVarHandlerMsg::StopAll => {
                                 handler.stop_all().await;
                                 break;
                             }
+                            ScriptVarHandlerMsg::Pause => {
+                                handler.pause().await;
+                            }
+                            ScriptVarHandlerMsg::Resume => {
+                                handler.resume().await;
+                            }
                         },
                         else => break,
                     };
@@ -85,6 +100,23 @@ impl ScriptVarHandlerHandle {
         );
     }
 
+    /// Restart a specific script-var if it is currently running. Script-vars that aren't running are left alone.
+    pub fn restart(&self, name: VarName) {
+        crate::print_result_err!(
+            "while forwarding instruction to script-var handler",
+            self.msg_send.send(ScriptVarHandlerMsg::Restart(name))
+        );
+    }
+
+    /// Update the script-vars to a new set of definitions, stopping the running script-vars that were removed or changed.
+    /// Changed and newly added script-vars start again once they are added via [`Self::add`].
+    pub fn reconcile(&self, script_vars: HashMap<VarName, ScriptVarDefinition>) {
+        crate::print_result_err!(
+            "while forwarding instruction to script-var handler",
+            self.msg_send.send(ScriptVarHandlerMsg::Reconcile(script_vars))
+        );
+    }
+
     /// Stop the execution of all script-vars.
     pub fn stop_all(&self) {
         crate::print_result_err!(
@@ -93,6 +125,23 @@ impl ScriptVarHandlerHandle {
         );
     }
 
+    /// Temporarily stop the execution of all script-vars, until [`Self::resume`] is called.
+    /// Script-vars added while paused only start running once resumed.
+    pub fn pause(&self) {
+        crate::print_result_err!(
+            "while forwarding instruction to script-var handler",
+            self.msg_send.send(ScriptVarHandlerMsg::Pause)
+        );
+    }
+
+    /// Restart all script-vars that were stopped by [`Self::pause`].
+    pub fn resume(&self) {
+        crate::print_result_err!(
+            "while forwarding instruction to script-var handler",
+            self.msg_send.send(ScriptVarHandlerMsg::Resume)
+        );
+    }
+
     pub fn join_thread(self) {
         let _ = self.thread_handle.join();
     }
@@ -104,17 +153,31 @@ impl ScriptVarHandlerHandle {
 enum ScriptVarHandlerMsg {
     AddVar(ScriptVarDefinition),
     Stop(VarName),
+    Restart(VarName),
+    Reconcile(HashMap<VarName, ScriptVarDefinition>),
     StopAll,
+    Pause,
+    Resume,
 }
 
 /// Handler that manages running and updating [ScriptVarDefinition]s
 struct ScriptVarHandler {
     listen_handler: ListenVarHandler,
     poll_handler: PollVarHandler,
+    /// All script-vars that should currently be running, including the ones stopped because the handler is paused.
+    running_vars: HashMap<VarName, ScriptVarDefinition>,
+    paused: bool,
 }
 
 impl ScriptVarHandler {
     async fn add(&mut self, script_var: ScriptVarDefinition) {
+        self.running_vars.insert(script_var.name().clone(), script_var.clone());
+        if !self.paused {
+            self.start(script_var).await;
+        }
+    }
+
+    async fn start(&mut self, script_var: ScriptVarDefinition) {
         match script_var {
             ScriptVarDefinition::Poll(var) => self.poll_handler.start(var).await,
             ScriptVarDefinition::Listen(var) => self.listen_handler.start(var).await,
@@ -124,17 +187,64 @@ impl ScriptVarHandler {
     /// Stop the handler that is responsible for a given variable.
     async fn stop_for_variable(&mut self, name: &VarName) -> Result<()> {
         log::debug!("Stopping script var process for variable {}", name);
+        self.running_vars.remove(name);
         self.listen_handler.stop_for_variable(name).await;
         self.poll_handler.stop_for_variable(name);
         Ok(())
     }
 
+    /// Stop and start a running script-var again, keeping it stopped if the handler is paused.
+    async fn restart(&mut self, name: &VarName) -> Result<()> {
+        if let Some(script_var) = self.running_vars.get(name).cloned() {
+            self.stop_for_variable(name).await?;
+            self.add(script_var).await;
+        }
+        Ok(())
+    }
+
+    /// Stop the running script-vars that are no longer defined the same way in the given definitions.
+    async fn reconcile(&mut self, script_vars: HashMap<VarName, ScriptVarDefinition>) -> Result<()> {
+        for name in outdated_script_vars(&self.running_vars, &script_vars) {
+            self.stop_for_variable(&name).await?;
+        }
+        Ok(())
+    }
+
     /// stop all running scripts and schedules
     async fn stop_all(&mut self) {
         log::debug!("Stopping script-var-handlers");
+        self.running_vars.clear();
         self.listen_handler.stop_all().await;
         self.poll_handler.stop_all();
     }
+
+    /// Stop all running scripts and schedules, but remember them so they can be restarted by [`Self::resume`].
+    async fn pause(&mut self) {
+        if !self.paused {
+            log::info!("Pausing all script-vars");
+            self.paused = true;
+            self.listen_handler.stop_all().await;
+            self.poll_handler.stop_all();
+        }
+    }
+
+    async fn resume(&mut self) {
+        if self.paused {
+            log::info!("Resuming all script-vars");
+            self.paused = false;
+            for var in self.running_vars.values().cloned().collect::<Vec<_>>() {
+                self.start(var).await;
+            }
+        }
+    }
+}
+
+/// Get the names of the running script-vars that were removed or changed in the new definitions.
+fn outdated_script_vars(
+    running_vars: &HashMap<VarName, ScriptVarDefinition>,
+    new_vars: &HashMap<VarName, ScriptVarDefinition>,
+) -> Vec<VarName> {
+    running_vars.iter().filter(|(name, var)| new_vars.get(*name) != Some(*var)).map(|(name, _)| name.clone()).collect()
 }
 
 struct PollVarHandler {
@@ -194,9 +304,8 @@ impl PollVarHandler {
 
 fn run_poll_once(var: &PollScriptVar) -> Result<DynVal> {
     match &var.command {
-        VarSource::Shell(span, command) => {
-            script_var::run_command(command).map_err(|e| anyhow!(create_script_var_failed_warn(*span, &var.name, &e.to_string())))
-        }
+        VarSource::Shell(span, command) => script_var::run_command(&var.name, command)
+            .map_err(|e| anyhow!(create_script_var_failed_warn(*span, &var.name, &e.to_string()))),
         VarSource::Function(x) => x().map_err(|e| anyhow!(e)),
     }
 }
@@ -259,11 +368,11 @@ impl ListenVarHandler {
                         evt_send.send(DaemonCommand::UpdateVars(vec![(var.name.to_owned(), new_value)]))?;
                     }
                     Ok(Some(line)) = stderr_lines.next_line() => {
-                        log::warn!("stderr of `{}`: {}", var.name, line);
+                        process_output::record(&format!("var {}", var.name), OutputStream::Stderr, &line);
                     }
                     else => break,
                 };
-                terminate_handle(handle).await;
+                terminate_handle(handle, &var.name).await;
 
                 if let Some(completion_notify) = completion_notify {
                     completion_notify.completed().await;
@@ -280,9 +389,9 @@ impl ListenVarHandler {
     }
 
     async fn stop_all(&mut self) {
-        for (_, token) in self.listen_process_handles.drain() {
-            token.cancel().await;
-        }
+        // Cancel all listen-vars at once, such that scripts that take a while to exit don't delay each other.
+        let cancellations = self.listen_process_handles.drain().map(|(_, token)| async move { token.cancel().await });
+        futures_util::future::join_all(cancellations).await;
     }
 }
 
@@ -304,13 +413,20 @@ impl Drop for ListenVarHandler {
     }
 }
 
-async fn terminate_handle(mut child: tokio::process::Child) {
+/// Environment variable to set how long scripts get to exit after being sent SIGTERM, before they get killed.
+const TERMINATE_TIMEOUT_ENV_VAR: &str = "EWW_TERMINATE_TIMEOUT";
+const DEFAULT_TERMINATE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Stop the process group of a script, killing it if it doesn't exit within the timeout after being sent SIGTERM.
+async fn terminate_handle(mut child: tokio::process::Child, name: &VarName) {
     if let Some(id) = child.id() {
         log::debug!("Killing process with id {}", id);
         let _ = signal::killpg(Pid::from_raw(id as i32), signal::SIGTERM);
         tokio::select! {
             _ = child.wait() => { },
-            _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => {
+            _ = tokio::time::sleep(get_terminate_timeout()) => {
+                log::warn!("Script of variable {} did not exit after being terminated, killing it", name);
+                let _ = signal::killpg(Pid::from_raw(id as i32), signal::SIGKILL);
                 let _ = child.kill().await;
             }
         };
@@ -319,6 +435,16 @@ async fn terminate_handle(mut child: tokio::process::Child) {
     }
 }
 
+fn get_terminate_timeout() -> Duration {
+    match std::env::var(TERMINATE_TIMEOUT_ENV_VAR) {
+        Ok(value) => DynVal::from_string(value.trim().to_string()).as_duration().unwrap_or_else(|err| {
+            log::warn!("Invalid value for {}: {}", TERMINATE_TIMEOUT_ENV_VAR, err);
+            DEFAULT_TERMINATE_TIMEOUT
+        }),
+        Err(_) => DEFAULT_TERMINATE_TIMEOUT,
+    }
+}
+
 // Especially for listenvars, we want to make sure that the scripts are actually
 // cancelled before we kill the tokio task that they run in.
 // for that, we need to wait for the completion of the cancel itself
@@ -355,3 +481,37 @@ mod cancellation {
         (AwaitableCancelationSender(send), AwaitableCancelationReceiver(recv))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eww_shared_util::Span;
+
+    fn listen_var(name: &str, command: &str) -> (VarName, ScriptVarDefinition) {
+        let var = ListenScriptVar {
+            name: VarName::from(name),
+            command: command.to_string(),
+            initial_value: DynVal::from_string(String::new()),
+            command_span: Span::DUMMY,
+            name_span: Span::DUMMY,
+        };
+        (VarName::from(name), ScriptVarDefinition::Listen(var))
+    }
+
+    #[test]
+    fn test_outdated_script_vars() {
+        let running = HashMap::from([listen_var("kept", "echo kept"), listen_var("removed", "echo removed")]);
+        let unchanged = HashMap::from([listen_var("kept", "echo kept"), listen_var("removed", "echo removed")]);
+        assert!(outdated_script_vars(&running, &unchanged).is_empty());
+
+        let mut added = unchanged.clone();
+        added.extend([listen_var("added", "echo added")]);
+        assert!(outdated_script_vars(&running, &added).is_empty());
+
+        let removed = HashMap::from([listen_var("kept", "echo kept")]);
+        assert_eq!(outdated_script_vars(&running, &removed), vec![VarName::from("removed")]);
+
+        let modified = HashMap::from([listen_var("kept", "echo changed"), listen_var("removed", "echo removed")]);
+        assert_eq!(outdated_script_vars(&running, &modified), vec![VarName::from("kept")]);
+    }
+}
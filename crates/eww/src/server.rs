@@ -2,19 +2,21 @@ use crate::{
     app::{self, DaemonCommand},
     config, daemon_response,
     display_backend::DisplayBackend,
-    error_handling_ctx, ipc_server, script_var_handler,
+    error_handling_ctx, ipc_server, persisted_vars, script_var_handler,
     state::scope_graph::ScopeGraph,
     EwwPaths,
 };
 use anyhow::{Context, Result};
+use gtk::prelude::CssProviderExt;
+use simple_signal::Signal;
 
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
     os::unix::io::AsRawFd,
-    path::Path,
+    path::{Path, PathBuf},
     rc::Rc,
-    sync::{atomic::Ordering, Arc},
+    time::Duration,
 };
 use tokio::sync::mpsc::*;
 
@@ -57,11 +59,22 @@ pub fn initialize_server<B: DisplayBackend>(
     "#
     );
 
-    simple_signal::set_handler(&[simple_signal::Signal::Int, simple_signal::Signal::Term], move |_| {
-        log::info!("Shutting down eww daemon...");
-        if let Err(e) = crate::application_lifecycle::send_exit() {
-            log::error!("Failed to send application shutdown event to workers: {:?}", e);
-            std::process::exit(1);
+    simple_signal::set_handler(&[Signal::Int, Signal::Term, Signal::Usr1, Signal::Usr2], {
+        let ui_send = ui_send.clone();
+        move |signals| {
+            if signals.iter().any(|signal| matches!(signal, Signal::Int | Signal::Term)) {
+                log::info!("Shutting down eww daemon...");
+                if let Err(e) = crate::application_lifecycle::send_exit() {
+                    log::error!("Failed to send application shutdown event to workers: {:?}", e);
+                    std::process::exit(1);
+                }
+            } else if signals.iter().any(|signal| matches!(signal, Signal::Usr1)) {
+                log::info!("Received SIGUSR1, reloading configuration");
+                reload_from_signal(&ui_send, DaemonCommand::ReloadConfigAndCss);
+            } else if signals.iter().any(|signal| matches!(signal, Signal::Usr2)) {
+                log::info!("Received SIGUSR2, reloading stylesheet");
+                reload_from_signal(&ui_send, DaemonCommand::ReloadCss);
+            }
         }
     });
 
@@ -72,23 +85,46 @@ pub fn initialize_server<B: DisplayBackend>(
 
     let (scope_graph_evt_send, mut scope_graph_evt_recv) = tokio::sync::mpsc::unbounded_channel();
 
+    let persisted_values = persisted_vars::read_for_config(paths.get_persisted_vars_file(), &eww_config);
+    let (config_dir_send, config_dir_recv) = tokio::sync::watch::channel(paths.config_dir.clone());
     let mut app = app::App {
         display_backend,
         scope_graph: Rc::new(RefCell::new(ScopeGraph::from_global_vars(
-            eww_config.generate_initial_state()?,
+            eww_config.generate_state_keeping(persisted_values)?,
             scope_graph_evt_send,
         ))),
         eww_config,
         open_windows: HashMap::new(),
+        instance_id_to_args: HashMap::new(),
+        saved_window_geometry: HashMap::new(),
         failed_windows: HashSet::new(),
+        var_waiters: Vec::new(),
+        var_subscribers: Vec::new(),
+        close_timers: HashMap::new(),
+        script_vars_paused: false,
         css_provider: gtk::CssProvider::new(),
+        color_css_provider: gtk::CssProvider::new(),
+        loaded_stylesheet: None,
         script_var_handler,
         app_evt_send: ui_send.clone(),
+        config_dir_send,
         paths,
     };
 
     if let Some(screen) = gdk::Screen::default() {
+        let default_css_provider = gtk::CssProvider::new();
+        crate::print_result_err!(
+            "loading the default stylesheet",
+            default_css_provider.load_from_data(config::scss::DEFAULT_CSS.as_bytes())
+        );
+        // Registered as a fallback, such that the GTK theme still takes precedence over these defaults.
+        gtk::StyleContext::add_provider_for_screen(&screen, &default_css_provider, gtk::STYLE_PROVIDER_PRIORITY_FALLBACK);
         gtk::StyleContext::add_provider_for_screen(&screen, &app.css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        gtk::StyleContext::add_provider_for_screen(
+            &screen,
+            &app.color_css_provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+        );
     }
 
     if let Ok((file_id, css)) = config::scss::parse_scss_from_config(app.paths.get_config_dir()) {
@@ -97,14 +133,33 @@ pub fn initialize_server<B: DisplayBackend>(
         }
     }
 
+    if let Some(display) = gdk::Display::default() {
+        let on_monitors_changed = {
+            let ui_send = ui_send.clone();
+            move || {
+                if let Err(err) = ui_send.send(DaemonCommand::MonitorsChanged) {
+                    log::error!("Failed to send monitor change to the app: {}", err);
+                }
+            }
+        };
+        display.connect_monitor_added({
+            let on_monitors_changed = on_monitors_changed.clone();
+            move |_, _| on_monitors_changed()
+        });
+        display.connect_monitor_removed(move |_, _| on_monitors_changed());
+    }
+
     // initialize all the handlers and tasks running asyncronously
-    init_async_part(app.paths.clone(), ui_send);
+    init_async_part(app.paths.clone(), config_dir_recv, ui_send);
 
     glib::MainContext::default().spawn_local(async move {
         // if an action was given to the daemon initially, execute it first.
         if let Some(action) = action {
             app.handle_command(action);
         }
+        for err in app.open_startup_windows() {
+            error_handling_ctx::print_error(err);
+        }
 
         loop {
             tokio::select! {
@@ -112,7 +167,15 @@ pub fn initialize_server<B: DisplayBackend>(
                     app.scope_graph.borrow_mut().handle_scope_graph_event(scope_graph_evt);
                 },
                 Some(ui_event) = ui_recv.recv() => {
-                    app.handle_command(ui_event);
+                    // Handle everything that queued up since the last iteration at once,
+                    // such that rapid updates of the same variable only cause a single update of the widgets.
+                    let mut commands = vec![ui_event];
+                    while let Ok(command) = ui_recv.try_recv() {
+                        commands.push(command);
+                    }
+                    for command in app::coalesce_var_updates(commands) {
+                        app.handle_command(command);
+                    }
                 }
                 else => break,
             }
@@ -125,7 +188,30 @@ pub fn initialize_server<B: DisplayBackend>(
     Ok(ForkResult::Child)
 }
 
-fn init_async_part(paths: EwwPaths, ui_send: UnboundedSender<app::DaemonCommand>) {
+/// Send a reload command to the app from the signal handler thread, and wait for the result.
+fn reload_from_signal(
+    ui_send: &UnboundedSender<DaemonCommand>,
+    command: fn(daemon_response::DaemonResponseSender) -> DaemonCommand,
+) {
+    let (daemon_resp_sender, mut daemon_resp_response) = daemon_response::create_pair();
+    let command = command(daemon_resp_sender.clone());
+    daemon_resp_sender.set_command_name(app::get_command_name(&command));
+    if let Err(err) = ui_send.send(command) {
+        log::error!("Failed to send reload command to the app: {}", err);
+        return;
+    }
+    match daemon_resp_response.blocking_recv() {
+        Some(daemon_response::DaemonResponse::Success(_)) => log::info!("Reloaded successfully"),
+        Some(daemon_response::DaemonResponse::Failure { message, .. }) => eprintln!("{}", message),
+        None => log::error!("No response to reload request"),
+    }
+}
+
+fn init_async_part(
+    paths: EwwPaths,
+    config_dir_recv: tokio::sync::watch::Receiver<PathBuf>,
+    ui_send: UnboundedSender<app::DaemonCommand>,
+) {
     std::thread::Builder::new()
         .name("outer-main-async-runtime".to_string())
         .spawn(move || {
@@ -138,8 +224,7 @@ fn init_async_part(paths: EwwPaths, ui_send: UnboundedSender<app::DaemonCommand>
             rt.block_on(async {
                 let filewatch_join_handle = {
                     let ui_send = ui_send.clone();
-                    let paths = paths.clone();
-                    tokio::spawn(async move { run_filewatch(paths.config_dir, ui_send).await })
+                    tokio::spawn(async move { run_filewatch(config_dir_recv, ui_send).await })
                 };
 
                 let ipc_server_join_handle = {
@@ -169,7 +254,11 @@ fn init_async_part(paths: EwwPaths, ui_send: UnboundedSender<app::DaemonCommand>
 }
 
 /// Watch configuration files for changes, sending reload events to the eww app when the files change.
-async fn run_filewatch<P: AsRef<Path>>(config_dir: P, evt_send: UnboundedSender<app::DaemonCommand>) -> Result<()> {
+/// The watched directory follows the configuration directory sent through `config_dir_recv`.
+async fn run_filewatch(
+    mut config_dir_recv: tokio::sync::watch::Receiver<PathBuf>,
+    evt_send: UnboundedSender<app::DaemonCommand>,
+) -> Result<()> {
     use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
@@ -188,41 +277,51 @@ async fn run_filewatch<P: AsRef<Path>>(config_dir: P, evt_send: UnboundedSender<
         Ok(_) => {}
         Err(e) => log::error!("Encountered Error While Watching Files: {}", e),
     })?;
-    watcher.watch(config_dir.as_ref(), RecursiveMode::Recursive)?;
-
-    // make sure to not trigger reloads too much by only accepting one reload every 500ms.
-    let debounce_done = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let mut config_dir = config_dir_recv.borrow_and_update().clone();
+    watcher.watch(&config_dir, RecursiveMode::Recursive)?;
 
     crate::loop_select_exiting! {
-        Some(()) = rx.recv() => {
-            let debounce_done = debounce_done.clone();
-            if debounce_done.swap(false, Ordering::SeqCst) {
-                tokio::spawn(async move {
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    debounce_done.store(true, Ordering::SeqCst);
-                });
-
-                let (daemon_resp_sender, mut daemon_resp_response) = daemon_response::create_pair();
-                // without this sleep, reading the config file sometimes gives an empty file.
-                // This is probably a result of editors not locking the file correctly,
-                // and eww being too fast, thus reading the file while it's empty.
-                // There should be some cleaner solution for this, but this will do for now.
-                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-                evt_send.send(app::DaemonCommand::ReloadConfigAndCss(daemon_resp_sender))?;
-                tokio::spawn(async move {
-                    match daemon_resp_response.recv().await {
-                        Some(daemon_response::DaemonResponse::Success(_)) => log::info!("Reloaded config successfully"),
-                        Some(daemon_response::DaemonResponse::Failure(e)) => eprintln!("{}", e),
-                        None => log::error!("No response to reload configuration-reload request"),
-                    }
-                });
+        // After switching to another configuration, only its directory is relevant.
+        Ok(()) = config_dir_recv.changed() => {
+            let new_config_dir = config_dir_recv.borrow_and_update().clone();
+            if let Err(err) = watcher.unwatch(&config_dir) {
+                log::warn!("Failed to stop watching {}: {}", config_dir.display(), err);
             }
+            if let Err(err) = watcher.watch(&new_config_dir, RecursiveMode::Recursive) {
+                log::error!("Failed to watch {} for changes: {}", new_config_dir.display(), err);
+            }
+            config_dir = new_config_dir;
+        },
+        // Editors often write files in multiple steps, so only reload once the files stopped changing.
+        // This also avoids reading a file while it is still empty.
+        Some(()) = debounce(&mut rx, FILEWATCH_DEBOUNCE_DURATION) => {
+            let (daemon_resp_sender, mut daemon_resp_response) = daemon_response::create_pair();
+            daemon_resp_sender.set_command_name("ReloadConfigAndCss");
+            evt_send.send(app::DaemonCommand::ReloadConfigAndCss(daemon_resp_sender))?;
+            tokio::spawn(async move {
+                match daemon_resp_response.recv().await {
+                    Some(daemon_response::DaemonResponse::Success(_)) => log::info!("Reloaded config successfully"),
+                    Some(daemon_response::DaemonResponse::Failure { message, .. }) => eprintln!("{}", message),
+                    None => log::error!("No response to reload configuration-reload request"),
+                }
+            });
         },
         else => break
     };
     Ok(())
 }
 
+/// How long the configuration files need to stay unchanged before they are reloaded.
+const FILEWATCH_DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
+
+/// Wait for an event, and then until no further event arrived for the given duration, coalescing all of them into one.
+/// Returns `None` once the channel is closed without any further event.
+async fn debounce<T>(rx: &mut UnboundedReceiver<T>, quiet_period: Duration) -> Option<()> {
+    rx.recv().await?;
+    while let Ok(Some(_)) = tokio::time::timeout(quiet_period, rx.recv()).await {}
+    Some(())
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ForkResult {
     Parent,
@@ -261,3 +360,30 @@ fn do_detach(log_file_path: impl AsRef<Path>) -> Result<ForkResult> {
 
     Ok(ForkResult::Child)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // With the clock paused, time only advances while all tasks are waiting, so scheduling delays can't affect the result.
+    #[tokio::test(start_paused = true)]
+    async fn test_debounce_coalesces_rapid_events() {
+        let quiet_period = Duration::from_millis(50);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let sender = tokio::spawn(async move {
+            for _ in 0..10 {
+                tx.send(()).unwrap();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            tx.send(()).unwrap();
+        });
+
+        let mut reloads = 0;
+        while debounce(&mut rx, quiet_period).await.is_some() {
+            reloads += 1;
+        }
+        sender.await.unwrap();
+        assert_eq!(reloads, 2);
+    }
+}
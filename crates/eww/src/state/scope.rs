@@ -1,8 +1,14 @@
 use anyhow::Result;
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use eww_shared_util::VarName;
+use eww_shared_util::{AttrName, VarName};
 use simplexpr::dynval::DynVal;
+use yuck::config::attributes::AttrEntry;
 
 use super::scope_graph::{ScopeGraph, ScopeIndex};
 
@@ -15,6 +21,8 @@ pub struct Scope {
     /// **Note** that there might be VarNames referenced here that are not defined in this scope.
     /// In those cases it is necessary to look into the scopes this scope is inheriting from.
     pub listeners: HashMap<VarName, Vec<Rc<Listener>>>,
+    /// The attributes of the widgets in this scope that were given an `:id`, by their id.
+    pub widget_attributes: HashMap<String, HashMap<AttrName, AttrEntry>>,
     pub node_index: ScopeIndex,
 }
 
@@ -22,7 +30,14 @@ impl Scope {
     /// Initializes a scope **incompletely**. The [`Self::node_index`] is not set correctly, and needs to be
     /// set to the index of the node in the scope graph that connects to this scope.
     pub(super) fn new(name: String, created_by: Option<ScopeIndex>, data: HashMap<VarName, DynVal>) -> Self {
-        Self { name, ancestor: created_by, data, listeners: HashMap::new(), node_index: ScopeIndex(0) }
+        Self {
+            name,
+            ancestor: created_by,
+            data,
+            listeners: HashMap::new(),
+            widget_attributes: HashMap::new(),
+            node_index: ScopeIndex(0),
+        }
     }
 }
 
@@ -31,9 +46,41 @@ pub type ListenerFn = Box<dyn Fn(&mut ScopeGraph, HashMap<VarName, DynVal>) -> R
 pub struct Listener {
     pub needed_variables: Vec<VarName>,
     pub f: ListenerFn,
+    /// If given, the listener is called at most once per interval.
+    /// Changes happening in between are coalesced into a single call once the interval has passed.
+    pub throttle: Option<Throttle>,
 }
 impl std::fmt::Debug for Listener {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Listener").field("needed_variables", &self.needed_variables).field("f", &"function").finish()
+        f.debug_struct("Listener")
+            .field("needed_variables", &self.needed_variables)
+            .field("f", &"function")
+            .field("throttle", &self.throttle)
+            .finish()
+    }
+}
+
+/// Keeps track of when a throttled [`Listener`] was last called.
+#[derive(Debug)]
+pub struct Throttle {
+    pub interval: Duration,
+    last_call: Cell<Option<Instant>>,
+    /// Whether a call of the listener is already scheduled for when the interval has passed.
+    pub(super) call_scheduled: Cell<bool>,
+}
+
+impl Throttle {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last_call: Cell::new(None), call_scheduled: Cell::new(false) }
+    }
+
+    /// Returns how long to wait before the listener may be called again, or `None` if it may be called right away.
+    pub(super) fn remaining_wait_time(&self) -> Option<Duration> {
+        let elapsed = self.last_call.get()?.elapsed();
+        (elapsed < self.interval).then(|| self.interval - elapsed)
+    }
+
+    pub(super) fn register_call(&self) {
+        self.last_call.set(Some(Instant::now()));
     }
 }
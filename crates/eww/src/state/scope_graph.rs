@@ -1,12 +1,14 @@
 use std::{
     collections::{HashMap, HashSet},
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use eww_shared_util::{AttrName, VarName};
+use itertools::Itertools;
 use simplexpr::{dynval::DynVal, SimplExpr};
 use tokio::sync::mpsc::UnboundedSender;
+use yuck::config::attributes::AttrEntry;
 
 use crate::error_handling_ctx;
 
@@ -28,6 +30,8 @@ impl ScopeIndex {
 
 pub enum ScopeGraphEvent {
     RemoveScope(ScopeIndex),
+    /// Call a throttled listener whose call was postponed until its throttle interval passed.
+    CallThrottledListener(ScopeIndex, Weak<Listener>),
 }
 
 /// A graph structure of scopes where each scope may inherit from another scope,
@@ -70,6 +74,7 @@ impl ScopeGraph {
             ancestor: None,
             data: vars,
             listeners: HashMap::new(),
+            widget_attributes: HashMap::new(),
             node_index: ScopeIndex(0),
         });
         if let Some(scope) = graph.scope_at_mut(root_index) {
@@ -87,6 +92,17 @@ impl ScopeGraph {
             ScopeGraphEvent::RemoveScope(scope_index) => {
                 self.remove_scope(scope_index);
             }
+            ScopeGraphEvent::CallThrottledListener(scope_index, listener) => {
+                // If the listener can't be upgraded anymore, its scope has been removed in the meantime.
+                if let Some(listener) = listener.upgrade() {
+                    if let Some(throttle) = &listener.throttle {
+                        throttle.call_scheduled.set(false);
+                    }
+                    if let Err(err) = self.call_listener(scope_index, &listener) {
+                        error_handling_ctx::print_error(err);
+                    }
+                }
+            }
         }
     }
 
@@ -98,6 +114,7 @@ impl ScopeGraph {
             ancestor: None,
             data: vars,
             listeners: HashMap::new(),
+            widget_attributes: HashMap::new(),
             node_index: ScopeIndex(0),
         });
         if let Some(scope) = self.graph.scope_at_mut(root_index) {
@@ -221,10 +238,7 @@ impl ScopeGraph {
                 scope.listeners.entry(required_var.clone()).or_default().push(listener.clone());
             }
 
-            let required_variables = self.lookup_variables_in_scope(scope_index, &listener.needed_variables)?;
-            if let Err(err) = (*listener.f)(self, required_variables).context("Error while updating UI after state change") {
-                error_handling_ctx::print_error(err);
-            }
+            self.call_listener(scope_index, &listener)?;
 
             #[cfg(debug_assertions)]
             self.validate()?;
@@ -233,6 +247,64 @@ impl ScopeGraph {
         Ok(())
     }
 
+    /// Describe the listeners registered in every scope, i.e. for runtime introspection.
+    pub fn describe_listeners(&self) -> serde_json::Value {
+        let scopes = self
+            .graph
+            .scopes()
+            .filter(|scope| !scope.listeners.is_empty())
+            .sorted_by_key(|scope| scope.node_index.0)
+            .map(|scope| {
+                let listeners = scope.listeners.values().flatten().unique_by(|listener| Rc::as_ptr(listener)).collect::<Vec<_>>();
+                let scheduled_calls = listeners
+                    .iter()
+                    .filter(|listener| listener.throttle.as_ref().map_or(false, |throttle| throttle.call_scheduled.get()))
+                    .count();
+                serde_json::json!({
+                    "scope": scope.name,
+                    "index": scope.node_index.0,
+                    "listeners": listeners.len(),
+                    "listeners_by_variable": scope
+                        .listeners
+                        .iter()
+                        .map(|(var_name, listeners)| (var_name.to_string(), listeners.len()))
+                        .collect::<HashMap<_, _>>(),
+                    "scheduled_throttled_calls": scheduled_calls,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(scopes)
+    }
+
+    /// Remember the attributes of a widget with an id in the scope it is used in, such that they can be inspected later.
+    pub fn register_widget_attributes(
+        &mut self,
+        scope_index: ScopeIndex,
+        widget_id: String,
+        attributes: HashMap<AttrName, AttrEntry>,
+    ) -> Result<()> {
+        let scope = self.graph.scope_at_mut(scope_index).context("Scope not in graph")?;
+        scope.widget_attributes.insert(widget_id, attributes);
+        Ok(())
+    }
+
+    /// Find the widget with the given id in a scope or any of its descendant scopes.
+    /// Returns the scope the widget is used in, together with the attributes of the widget.
+    pub fn find_widget_attributes(
+        &self,
+        scope_index: ScopeIndex,
+        widget_id: &str,
+    ) -> Option<(ScopeIndex, &HashMap<AttrName, AttrEntry>)> {
+        let scope = self.graph.scope_at(scope_index)?;
+        if let Some(attributes) = scope.widget_attributes.get(widget_id) {
+            return Some((scope_index, attributes));
+        }
+        self.graph
+            .descendant_edges_of(scope_index)
+            .into_iter()
+            .find_map(|(descendant, _)| self.find_widget_attributes(descendant, widget_id))
+    }
+
     /// Register the fact that a scope is referencing a given variable.
     /// If the scope contains the variable itself, this is a No-op. Otherwise, will add that reference to the inherited scope relation.
     pub fn register_scope_referencing_variable(&mut self, scope_index: ScopeIndex, var_name: VarName) -> Result<()> {
@@ -291,15 +363,39 @@ impl ScopeGraph {
         let scope = self.graph.scope_at(scope_index).context("Scope not in graph")?;
         if let Some(triggered_listeners) = scope.listeners.get(updated_var) {
             for listener in triggered_listeners.clone() {
-                let required_variables = self.lookup_variables_in_scope(scope_index, &listener.needed_variables)?;
-                if let Err(err) = (*listener.f)(self, required_variables).context("Error while updating UI after state change") {
-                    error_handling_ctx::print_error(err);
-                }
+                self.call_listener(scope_index, &listener)?;
             }
         }
         Ok(())
     }
 
+    /// Call a listener with the current values of the variables it needs.
+    /// If the listener is throttled and was called too recently, the call is instead scheduled
+    /// to happen once the throttle interval has passed, using the values of the variables at that time.
+    fn call_listener(&mut self, scope_index: ScopeIndex, listener: &Rc<Listener>) -> Result<()> {
+        if let Some(throttle) = &listener.throttle {
+            if throttle.call_scheduled.get() {
+                return Ok(());
+            }
+            if let Some(remaining_wait_time) = throttle.remaining_wait_time() {
+                throttle.call_scheduled.set(true);
+                let event_sender = self.event_sender.clone();
+                let listener = Rc::downgrade(listener);
+                glib::timeout_add_local_once(remaining_wait_time, move || {
+                    let _ = event_sender.send(ScopeGraphEvent::CallThrottledListener(scope_index, listener));
+                });
+                return Ok(());
+            }
+            throttle.register_call();
+        }
+
+        let required_variables = self.lookup_variables_in_scope(scope_index, &listener.needed_variables)?;
+        if let Err(err) = (*listener.f)(self, required_variables).context("Error while updating UI after state change") {
+            error_handling_ctx::print_error(err);
+        }
+        Ok(())
+    }
+
     /// Find the closest available scope that contains variable with the given name.
     pub fn find_scope_with_variable(&self, index: ScopeIndex, var_name: &VarName) -> Option<ScopeIndex> {
         let scope = self.graph.scope_at(index)?;
@@ -466,6 +562,10 @@ mod internal {
             self.scopes.get(&index)
         }
 
+        pub fn scopes(&self) -> impl Iterator<Item = &Scope> {
+            self.scopes.values()
+        }
+
         pub fn scope_at_mut(&mut self, index: ScopeIndex) -> Option<&mut Scope> {
             self.scopes.get_mut(&index)
         }
@@ -1,7 +1,12 @@
-use super::scope::Listener;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use super::scope::{Listener, Throttle};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 use eww_shared_util::{Span, VarName};
@@ -22,6 +27,7 @@ macro_rules! make_listener {
     (|$($varname:expr => $name:ident),*| $body:block) => {
         Listener {
             needed_variables: vec![$($varname),*],
+            throttle: None,
             f: Box::new(move |_, values| {
                 $(
                     let $name = values.get(&$varname).unwrap();
@@ -156,3 +162,38 @@ fn test_state_updates() {
     scope_graph.update_value(scope_graph.root_index, &"global_2".into(), "new global 2".into()).unwrap();
     assert!(bar_2_verify.load(Ordering::Relaxed), "inherited global update did not trigger properly");
 }
+
+#[test]
+fn test_throttled_listener() {
+    let globals = hashmap! {
+        "global_1".into() => DynVal::from("initial"),
+    };
+
+    let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
+
+    let mut scope_graph = ScopeGraph::from_global_vars(globals, send);
+
+    let received_values = Rc::new(RefCell::new(Vec::<DynVal>::new()));
+    let listener = Listener {
+        needed_variables: vec!["global_1".into()],
+        f: Box::new({
+            let received_values = received_values.clone();
+            move |_, values| {
+                received_values.borrow_mut().push(values.get(&VarName::from("global_1")).unwrap().clone());
+                Ok(())
+            }
+        }),
+        throttle: Some(Throttle::new(Duration::from_millis(50))),
+    };
+    scope_graph.register_listener(scope_graph.root_index, listener).unwrap();
+
+    scope_graph.update_global_value(&"global_1".into(), "first".into()).unwrap();
+    scope_graph.update_global_value(&"global_1".into(), "second".into()).unwrap();
+    assert_eq!(*received_values.borrow(), vec![DynVal::from("initial")], "throttled listener got called too early");
+
+    std::thread::sleep(Duration::from_millis(60));
+    glib::MainContext::default().iteration(false);
+    let event = recv.try_recv().expect("throttled listener call did not get scheduled");
+    scope_graph.handle_scope_graph_event(event);
+    assert_eq!(*received_values.borrow(), vec![DynVal::from("initial"), DynVal::from("second")]);
+}
@@ -7,7 +7,9 @@ macro_rules! try_logging_errors {
     ($context:expr => $code:block) => {{
         let result: Result<_> = try { $code };
         if let Err(err) = result {
-            log::error!("[{}:{}] Error while {}: {:?}", ::std::file!(), ::std::line!(), $context, err);
+            let context = $context;
+            log::error!("[{}:{}] Error while {}: {:?}", ::std::file!(), ::std::line!(), context, err);
+            $crate::error_handling_ctx::record_error(context, $crate::daemon_response::ErrorKind::of(&err), format!("{:#}", err));
         }
     }};
 }
@@ -16,7 +18,9 @@ macro_rules! try_logging_errors {
 macro_rules! print_result_err {
     ($context:expr, $result:expr $(,)?) => {{
         if let Err(err) = $result {
-            log::error!("[{}:{}] Error {}: {:?}", ::std::file!(), ::std::line!(), $context, err);
+            let context = $context;
+            log::error!("[{}:{}] Error {}: {:?}", ::std::file!(), ::std::line!(), context, err);
+            $crate::error_handling_ctx::record_error(context, $crate::daemon_response::ErrorKind::Other, format!("{:?}", err));
         }
     }};
 }
@@ -135,6 +135,13 @@ fn build_builtin_gtk_widget(
         widget_defs,
         custom_widget_invocation,
     };
+
+    // Remember the attributes of widgets with an id, such that their values can be inspected via `eww get-attr`.
+    if let Some(widget_id) = bargs.widget_use.attrs.primitive_optional::<String, _>("id")? {
+        bargs.unhandled_attrs.remove(&AttrName("id".to_string()));
+        bargs.scope_graph.register_widget_attributes(calling_scope, widget_id, bargs.unhandled_attrs.clone())?;
+    }
+
     let gtk_widget = widget_definitions::widget_use_to_gtk_widget(&mut bargs)?;
 
     if let Some(gtk_container) = gtk_widget.dynamic_cast_ref::<gtk::Container>() {
@@ -227,6 +234,7 @@ fn build_loop_special_widget(
         calling_scope,
         Listener {
             needed_variables: widget_use.elements_expr.collect_var_refs(),
+            throttle: None,
             f: Box::new({
                 let elements_expr = widget_use.elements_expr.clone();
                 let elements_expr_span = widget_use.elements_expr_span;
@@ -299,6 +307,7 @@ fn build_children_special_widget(
             calling_scope,
             Listener {
                 needed_variables: nth.collect_var_refs(),
+                throttle: None,
                 f: Box::new({
                     move |tree, values| {
                         let nth_value = nth.eval(&values)?.as_i32()?;
@@ -1,3 +1,19 @@
+use eww_shared_util::Spanned;
+use simplexpr::{dynval::ConversionError, SimplExpr};
+use yuck::{error::DiagError, format_diagnostic::DiagnosticExt, gen_diagnostic};
+
+/// Generate an error for a widget attribute whose value could not be converted into the type the widget expects.
+pub fn attr_conversion_error(widget_name: &str, attr_name: &str, expr: &SimplExpr, err: ConversionError) -> DiagError {
+    let diag = gen_diagnostic! {
+        msg = format!("Invalid value for attribute `{}` of widget `{}`", attr_name, widget_name),
+        label = expr.span() => format!("Expected a value of type `{}`, but got `{}`", err.target_type, err.value),
+    };
+    DiagError(match err.source {
+        Some(source) => diag.with_note(source.to_string()),
+        None => diag,
+    })
+}
+
 #[macro_export]
 macro_rules! def_widget {
     ($args:ident, $scope_graph:ident, $gtk_widget:ident, {
@@ -11,7 +27,10 @@ macro_rules! def_widget {
             $(
                 // explicitly box the function to not cause tons of monomorphization related duplications of Vec::retain
                 let retain_fn: Box<dyn Fn(&eww_shared_util::wrappers::AttrName, &mut yuck::config::attributes::AttrEntry) -> bool> =
-                    Box::new(|a, _| &a.0 != &::std::stringify!($attr_name).replace('_', "-"));
+                    Box::new(|a, _| {
+                        let attr_name = ::std::stringify!($attr_name).replace('_', "-");
+                        a.0 != attr_name && a.0 != format!("{}-throttle", attr_name)
+                    });
                 $args.unhandled_attrs.retain(retain_fn);
             )*
 
@@ -38,14 +57,27 @@ macro_rules! def_widget {
                         .flat_map(|expr| expr.as_ref().map(|x| x.collect_var_refs()).unwrap_or_default())
                         .collect();
 
+                    // Any of the attributes may declare a minimum interval between updates via `:<attr>-throttle`.
+                    // As all attributes of this `prop` get updated together, the longest given interval is used.
+                    let mut throttle_interval: Option<std::time::Duration> = None;
+                    $(
+                        let throttle_attr = format!("{}-throttle", ::std::stringify!($attr_name).replace('_', "-"));
+                        if let Some(interval) = $args.widget_use.attrs.primitive_optional::<String, _>(&throttle_attr)? {
+                            let interval = simplexpr::dynval::DynVal::from_string(interval).as_duration()?;
+                            throttle_interval = Some(throttle_interval.map_or(interval, |current| current.max(interval)));
+                        }
+                    )*
+
                     $args.scope_graph.register_listener(
                         $args.calling_scope,
                             $crate::state::scope::Listener {
                             needed_variables: required_vars,
+                            throttle: throttle_interval.map($crate::state::scope::Throttle::new),
                             f: Box::new({
                                 // create a weak reference to the widget, such that this listener doesn't prevent the actual widget from
                                 // getting deallocated (garbage collected by the gtk runtime)
                                 let $gtk_widget = gdk::glib::clone::Downgrade::downgrade(&$gtk_widget);
+                                let widget_name = $args.widget_use.name.clone();
                                 move |$scope_graph, values| {
                                     // TODO when this fails, shouldn't we technically remove the listener somehow? Need to analyze when exactly this happens.
                                     let $gtk_widget = gdk::glib::clone::Upgrade::upgrade(&$gtk_widget)
@@ -62,7 +94,10 @@ macro_rules! def_widget {
 
                                         // if the value is Some, evaluate and typecast it as expected
                                         let $attr_name = if let Some(x) = $attr_name {
-                                            Some(x.eval(&values)?.$typecast_func()?)
+                                            Some(x.eval(&values)?.$typecast_func().map_err(|e| {
+                                                let attr_name = ::std::stringify!($attr_name).replace('_', "-");
+                                                $crate::widgets::def_widget_macro::attr_conversion_error(&widget_name, &attr_name, x, e)
+                                            })?)
                                         } else {
                                             None
                                         };
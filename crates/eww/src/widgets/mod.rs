@@ -1,4 +1,6 @@
-use std::process::Command;
+use std::process::{Command, Stdio};
+
+use crate::process_output::{self, OutputStream};
 
 pub mod build_widget;
 pub mod circular_progressbar;
@@ -6,12 +8,14 @@ pub mod def_widget_macro;
 pub mod graph;
 pub mod transform;
 pub mod widget_definitions;
+pub mod widget_docs;
 
 /// Run a command that was provided as an attribute.
 /// This command may use placeholders which will be replaced by the values of the arguments given.
 /// This can either be the placeholder `{}`, which will be replaced by the first argument,
 /// Or a placeholder like `{0}`, `{1}`, etc, which will refer to the respective argument.
-pub(self) fn run_command<T>(timeout: std::time::Duration, cmd: &str, args: &[T])
+/// The stderr of the command is recorded as process output of the given source, naming the widget and the attribute.
+pub(self) fn run_command<T>(timeout: std::time::Duration, source: &'static str, cmd: &str, args: &[T])
 where
     T: 'static + std::fmt::Display + Send + Sync + Clone,
 {
@@ -21,18 +25,26 @@ where
         .name("command-execution-thread".to_string())
         .spawn(move || {
             log::debug!("Running command from widget [timeout: {}ms]: {}", timeout.as_millis(), cmd);
-            let child = Command::new("/bin/sh").arg("-c").arg(&cmd).spawn();
+            // Without a free forwarder thread, the command just inherits the stderr of eww.
+            let forwarder_slot = process_output::reserve_command_forwarder();
+            let stderr = if forwarder_slot.is_some() { Stdio::piped() } else { Stdio::inherit() };
+            let child = Command::new("/bin/sh").arg("-c").arg(&cmd).stderr(stderr).spawn();
             match child {
-                Ok(mut child) => match child.wait_timeout(timeout) {
-                    // child timed out
-                    Ok(None) => {
-                        log::error!("WARNING: command {} timed out", &cmd);
-                        let _ = child.kill();
-                        let _ = child.wait();
+                Ok(mut child) => {
+                    if let (Some(stderr), Some(slot)) = (child.stderr.take(), forwarder_slot) {
+                        process_output::forward_lines(source.to_string(), OutputStream::Stderr, stderr, slot);
+                    }
+                    match child.wait_timeout(timeout) {
+                        // child timed out
+                        Ok(None) => {
+                            log::error!("WARNING: command {} timed out", &cmd);
+                            let _ = child.kill();
+                            let _ = child.wait();
+                        }
+                        Err(err) => log::error!("Failed to execute command {}: {}", cmd, err),
+                        Ok(Some(_)) => {}
                     }
-                    Err(err) => log::error!("Failed to execute command {}: {}", cmd, err),
-                    Ok(Some(_)) => {}
-                },
+                }
                 Err(err) => log::error!("Failed to launch child process: {}", err),
             }
         })
@@ -253,7 +253,7 @@ pub(super) fn resolve_range_attrs(bargs: &mut BuilderArgs, gtk_widget: &gtk::Ran
             connect_signal_handler!(gtk_widget, gtk_widget.connect_value_changed(move |gtk_widget| {
                 let value = gtk_widget.value();
                 if last_set_value.borrow_mut().take() != Some(value) {
-                    run_command(timeout, &onchange, &[value]);
+                    run_command(timeout, "range :onchange", &onchange, &[value]);
                 }
             }));
         }
@@ -289,7 +289,8 @@ fn build_gtk_combo_box_text(bargs: &mut BuilderArgs) -> Result<gtk::ComboBoxText
         // @prop onchange - runs the code when a item was selected, replacing {} with the item as a string
         prop(timeout: as_duration = Duration::from_millis(200), onchange: as_string) {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_changed(move |gtk_widget| {
-                run_command(timeout, &onchange, &[gtk_widget.active_text().unwrap_or_else(|| "".into())]);
+                let active_text = gtk_widget.active_text().unwrap_or_else(|| "".into());
+                run_command(timeout, "combo-box-text :onchange", &onchange, &[active_text]);
             }));
         },
     });
@@ -327,7 +328,7 @@ fn build_gtk_revealer(bargs: &mut BuilderArgs) -> Result<gtk::Revealer> {
 }
 
 const WIDGET_NAME_CHECKBOX: &str = "checkbox";
-/// @widget a checkbox
+/// @widget checkbox
 /// @desc A checkbox that can trigger events on checked / unchecked.
 fn build_gtk_checkbox(bargs: &mut BuilderArgs) -> Result<gtk::CheckButton> {
     let gtk_widget = gtk::CheckButton::new();
@@ -337,7 +338,11 @@ fn build_gtk_checkbox(bargs: &mut BuilderArgs) -> Result<gtk::CheckButton> {
         // @prop onunchecked - similar to onchecked but when the widget is unchecked
         prop(timeout: as_duration = Duration::from_millis(200), onchecked: as_string = "", onunchecked: as_string = "") {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_toggled(move |gtk_widget| {
-                run_command(timeout, if gtk_widget.is_active() { &onchecked } else { &onunchecked }, &[] as &[&str]);
+                if gtk_widget.is_active() {
+                    run_command(timeout, "checkbox :onchecked", &onchecked, &[] as &[&str]);
+                } else {
+                    run_command(timeout, "checkbox :onunchecked", &onunchecked, &[] as &[&str]);
+                }
             }));
        }
     });
@@ -358,7 +363,7 @@ fn build_gtk_color_button(bargs: &mut BuilderArgs) -> Result<gtk::ColorButton> {
         // @prop timeout - timeout of the command. Default: "200ms"
         prop(timeout: as_duration = Duration::from_millis(200), onchange: as_string) {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_color_set(move |gtk_widget| {
-                run_command(timeout, &onchange, &[gtk_widget.rgba()]);
+                run_command(timeout, "color-button :onchange", &onchange, &[gtk_widget.rgba()]);
             }));
         }
     });
@@ -379,7 +384,7 @@ fn build_gtk_color_chooser(bargs: &mut BuilderArgs) -> Result<gtk::ColorChooserW
         // @prop timeout - timeout of the command. Default: "200ms"
         prop(timeout: as_duration = Duration::from_millis(200), onchange: as_string) {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_color_activated(move |_a, color| {
-                run_command(timeout, &onchange, &[*color]);
+                run_command(timeout, "color-chooser :onchange", &onchange, &[*color]);
             }));
         }
     });
@@ -449,14 +454,14 @@ fn build_gtk_input(bargs: &mut BuilderArgs) -> Result<gtk::Entry> {
         // @prop timeout - timeout of the command. Default: "200ms"
         prop(timeout: as_duration = Duration::from_millis(200), onchange: as_string) {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_changed(move |gtk_widget| {
-                run_command(timeout, &onchange, &[gtk_widget.text().to_string()]);
+                run_command(timeout, "input :onchange", &onchange, &[gtk_widget.text().to_string()]);
             }));
         },
         // @prop onaccept - Command to run when the user hits return in the input field. The placeholder `{}` will be replaced by the value
         // @prop timeout - timeout of the command. Default: "200ms"
         prop(timeout: as_duration = Duration::from_millis(200), onaccept: as_string) {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_activate(move |gtk_widget| {
-                run_command(timeout, &onaccept, &[gtk_widget.text().to_string()]);
+                run_command(timeout, "input :onaccept", &onaccept, &[gtk_widget.text().to_string()]);
             }));
         },
         // @prop password - if the input is obscured
@@ -487,9 +492,9 @@ fn build_gtk_button(bargs: &mut BuilderArgs) -> Result<gtk::Button> {
             gtk_widget.add_events(gdk::EventMask::BUTTON_PRESS_MASK);
             connect_signal_handler!(gtk_widget, gtk_widget.connect_button_press_event(move |_, evt| {
                 match evt.button() {
-                    1 => run_command(timeout, &onclick, &[] as &[&str]),
-                    2 => run_command(timeout, &onmiddleclick, &[] as &[&str]),
-                    3 => run_command(timeout, &onrightclick, &[] as &[&str]),
+                    1 => run_command(timeout, "button :onclick", &onclick, &[] as &[&str]),
+                    2 => run_command(timeout, "button :onmiddleclick", &onmiddleclick, &[] as &[&str]),
+                    3 => run_command(timeout, "button :onrightclick", &onrightclick, &[] as &[&str]),
                     _ => {},
                 }
                 gtk::Inhibit(false)
@@ -683,7 +688,7 @@ fn build_gtk_event_box(bargs: &mut BuilderArgs) -> Result<gtk::EventBox> {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_scroll_event(move |_, evt| {
                 let delta = evt.delta().1;
                 if delta != 0f64 { // Ignore the first event https://bugzilla.gnome.org/show_bug.cgi?id=675959
-                    run_command(timeout, &onscroll, &[if delta < 0f64 { "up" } else { "down" }]);
+                    run_command(timeout, "eventbox :onscroll", &onscroll, &[if delta < 0f64 { "up" } else { "down" }]);
                 }
                 gtk::Inhibit(false)
             }));
@@ -694,7 +699,7 @@ fn build_gtk_event_box(bargs: &mut BuilderArgs) -> Result<gtk::EventBox> {
             gtk_widget.add_events(gdk::EventMask::ENTER_NOTIFY_MASK);
             connect_signal_handler!(gtk_widget, gtk_widget.connect_enter_notify_event(move |_, evt| {
                 if evt.detail() != NotifyType::Inferior {
-                    run_command(timeout, &onhover, &[evt.position().0, evt.position().1]);
+                    run_command(timeout, "eventbox :onhover", &onhover, &[evt.position().0, evt.position().1]);
                 }
                 gtk::Inhibit(false)
             }));
@@ -705,7 +710,7 @@ fn build_gtk_event_box(bargs: &mut BuilderArgs) -> Result<gtk::EventBox> {
             gtk_widget.add_events(gdk::EventMask::LEAVE_NOTIFY_MASK);
             connect_signal_handler!(gtk_widget, gtk_widget.connect_leave_notify_event(move |_, evt| {
                 if evt.detail() != NotifyType::Inferior {
-                    run_command(timeout, &onhoverlost, &[evt.position().0, evt.position().1]);
+                    run_command(timeout, "eventbox :onhoverlost", &onhoverlost, &[evt.position().0, evt.position().1]);
                 }
                 gtk::Inhibit(false)
             }));
@@ -748,9 +753,9 @@ fn build_gtk_event_box(bargs: &mut BuilderArgs) -> Result<gtk::EventBox> {
             );
             connect_signal_handler!(gtk_widget, gtk_widget.connect_drag_data_received(move |_, _, _x, _y, selection_data, _target_type, _timestamp| {
                 if let Some(data) = selection_data.uris().first(){
-                    run_command(timeout, &ondropped, &[data.to_string(), "file".to_string()]);
+                    run_command(timeout, "eventbox :ondropped", &ondropped, &[data.to_string(), "file".to_string()]);
                 } else if let Some(data) = selection_data.text(){
-                    run_command(timeout, &ondropped, &[data.to_string(), "text".to_string()]);
+                    run_command(timeout, "eventbox :ondropped", &ondropped, &[data.to_string(), "text".to_string()]);
                 }
             }));
         },
@@ -797,9 +802,9 @@ fn build_gtk_event_box(bargs: &mut BuilderArgs) -> Result<gtk::EventBox> {
             gtk_widget.add_events(gdk::EventMask::BUTTON_PRESS_MASK);
             connect_signal_handler!(gtk_widget, gtk_widget.connect_button_press_event(move |_, evt| {
                 match evt.button() {
-                    1 => run_command(timeout, &onclick, &[] as &[&str]),
-                    2 => run_command(timeout, &onmiddleclick, &[] as &[&str]),
-                    3 => run_command(timeout, &onrightclick, &[] as &[&str]),
+                    1 => run_command(timeout, "eventbox :onclick", &onclick, &[] as &[&str]),
+                    2 => run_command(timeout, "eventbox :onmiddleclick", &onmiddleclick, &[] as &[&str]),
+                    3 => run_command(timeout, "eventbox :onrightclick", &onrightclick, &[] as &[&str]),
                     _ => {},
                 }
                 gtk::Inhibit(false)
@@ -953,6 +958,7 @@ fn build_gtk_calendar(bargs: &mut BuilderArgs) -> Result<gtk::Calendar> {
             connect_signal_handler!(gtk_widget, gtk_widget.connect_day_selected(move |w| {
                 run_command(
                     timeout,
+                    "calendar :onclick",
                     &onclick,
                     &[w.day(), w.month(), w.year()]
                 )
@@ -0,0 +1,129 @@
+//! Documentation of the builtin widgets and their properties.
+//!
+//! This is read from the `@widget`, `@desc` and `@prop` comments in [`super::widget_definitions`],
+//! which are also used to generate the widget documentation.
+//! As the source is embedded at compile time, this always matches the widgets supported by the running binary.
+
+use itertools::Itertools;
+
+const WIDGET_DEFINITIONS_SOURCE: &str = include_str!("widget_definitions.rs");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WidgetDoc {
+    pub name: String,
+    pub desc: String,
+    /// Names of the (hidden) widgets whose properties this widget supports as well
+    pub extends: Vec<String>,
+    pub props: Vec<PropDoc>,
+    /// Hidden widgets only exist to share properties between widgets, and can't be used directly
+    pub is_visible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropDoc {
+    pub name: String,
+    pub type_name: Option<String>,
+    pub desc: String,
+}
+
+impl std::fmt::Display for PropDoc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} - {}", self.name, self.type_name.as_deref().unwrap_or("unknown"), self.desc)
+    }
+}
+
+/// Parse the documentation comments of all builtin widgets.
+pub fn builtin_widget_docs() -> Vec<WidgetDoc> {
+    let lines = WIDGET_DEFINITIONS_SOURCE.lines().collect::<Vec<_>>();
+    let mut widgets: Vec<WidgetDoc> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let comment = match comment_content(line) {
+            Some(comment) => comment,
+            None => continue,
+        };
+        if let Some(widget) = comment.strip_prefix("@widget ") {
+            let widget = widget.trim();
+            let (is_visible, widget) = match widget.strip_prefix('!') {
+                Some(widget) => (false, widget),
+                None => (true, widget),
+            };
+            let (name, extends) = match widget.split_once(" extends ") {
+                Some((name, extends)) => (name.trim(), extends.split(',').map(|x| x.trim().to_string()).collect()),
+                None => (widget, Vec::new()),
+            };
+            widgets.push(WidgetDoc { name: name.to_string(), desc: String::new(), extends, props: Vec::new(), is_visible });
+        } else if let Some(desc) = comment.strip_prefix("@desc ") {
+            if let Some(widget) = widgets.last_mut() {
+                widget.desc = desc.trim().to_string();
+            }
+        } else if let Some(prop) = comment.strip_prefix("@prop ") {
+            if let (Some(widget), Some((name, desc))) = (widgets.last_mut(), prop.split_once(" - ")) {
+                let name = name.trim();
+                if !widget.props.iter().any(|p| p.name == name) {
+                    let type_name = find_prop_type(&lines[index + 1..], name);
+                    widget.props.push(PropDoc { name: name.to_string(), type_name, desc: desc.trim().to_string() });
+                }
+            }
+        }
+    }
+    widgets
+}
+
+/// Format the documentation of all usable builtin widgets, including the properties they inherit.
+pub fn format_builtin_widget_docs() -> String {
+    let docs = builtin_widget_docs();
+    docs.iter()
+        .filter(|widget| widget.is_visible)
+        .map(|widget| {
+            let inherited = widget.extends.iter().filter_map(|name| docs.iter().find(|w| &w.name == name));
+            let props = widget.props.iter().chain(inherited.flat_map(|w| w.props.iter())).map(|prop| format!("  {}", prop));
+            std::iter::once(format!("{} - {}", widget.name, widget.desc)).chain(props).join("\n")
+        })
+        .join("\n\n")
+}
+
+/// Returns the content of a `//`-comment line, without the leading slashes.
+fn comment_content(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    line.starts_with("//").then(|| line.trim_start_matches('/').trim_start())
+}
+
+/// Find the type of a property in the first line of code after its documentation comment,
+/// which is expected to look like `prop(name: as_type, ...)`.
+fn find_prop_type(following_lines: &[&str], prop_name: &str) -> Option<String> {
+    let code: &str = following_lines.iter().find(|line| comment_content(line).is_none())?;
+    let prop_name = prop_name.replace('-', "_");
+    let type_name = code.match_indices(": as_").find_map(|(index, pattern)| {
+        let name_start = code[..index].rfind(|c: char| !(c.is_alphanumeric() || c == '_')).map_or(0, |i| i + 1);
+        (code[name_start..index] == prop_name)
+            .then(|| code[index + pattern.len()..].chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect::<String>())
+    })?;
+    let type_name = match type_name.as_str() {
+        "f64" | "f32" => "float",
+        "i32" | "i64" => "int",
+        other => other,
+    };
+    Some(type_name.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::widgets::widget_definitions::BUILTIN_WIDGET_NAMES;
+
+    #[test]
+    fn test_all_builtin_widgets_documented() {
+        let docs = builtin_widget_docs();
+        for name in BUILTIN_WIDGET_NAMES {
+            assert!(docs.iter().any(|widget| widget.is_visible && widget.name == *name), "widget {} is not documented", name);
+        }
+    }
+
+    #[test]
+    fn test_find_prop_type() {
+        let lines = ["// some comment", "prop(min_width: as_i32, width: as_f64) {"];
+        assert_eq!(find_prop_type(&lines, "width"), Some("float".to_string()));
+        assert_eq!(find_prop_type(&lines, "min-width"), Some("int".to_string()));
+        assert_eq!(find_prop_type(&lines, "height"), None);
+    }
+}
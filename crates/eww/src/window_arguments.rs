@@ -0,0 +1,69 @@
+use anyhow::{bail, Result};
+use eww_shared_util::{AttrName, VarName};
+use simplexpr::{dynval::DynVal, SimplExpr};
+use std::{collections::HashMap, time::Duration};
+use yuck::{
+    config::{monitor::MonitorIdentifier, window_definition::WindowDefinition, window_geometry::AnchorPoint},
+    value::Coords,
+};
+
+/// The arguments a window instance was opened with.
+///
+/// These are kept for every open window, such that the window can be recreated
+/// exactly the same way when the configuration is reloaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowArguments {
+    /// Name of the window definition this instance was created from
+    pub window_name: String,
+    /// Id of this instance. This is the name of the window, unless explicitly specified.
+    pub instance_id: String,
+    pub anchor: Option<AnchorPoint>,
+    pub monitor: Option<MonitorIdentifier>,
+    pub pos: Option<Coords>,
+    pub size: Option<Coords>,
+    /// Close the window again once this duration has passed
+    pub close_after: Option<Duration>,
+    /// Whether the anchor, position and size were given explicitly, rather than computed by eww,
+    /// such that they are remembered for the next time this instance is opened
+    pub remember_geometry: bool,
+    /// Values for the arguments declared in the window definition
+    pub args: HashMap<VarName, DynVal>,
+}
+
+impl WindowArguments {
+    /// Arguments for opening a window by its name, without any further configuration.
+    pub fn new_from_name(window_name: String) -> Self {
+        Self {
+            instance_id: window_name.clone(),
+            window_name,
+            anchor: None,
+            monitor: None,
+            pos: None,
+            size: None,
+            close_after: None,
+            remember_geometry: false,
+            args: HashMap::new(),
+        }
+    }
+
+    /// Check the given arguments against the ones declared by the window definition,
+    /// returning the attributes that should be provided to the scope of the window.
+    pub fn get_local_window_variables(&self, window_def: &WindowDefinition) -> Result<HashMap<AttrName, SimplExpr>> {
+        if let Some(unknown) = self.args.keys().find(|name| !window_def.expected_args.iter().any(|arg| arg.name.0 == name.0)) {
+            bail!("Window `{}` does not take an argument called `{}`", self.window_name, unknown);
+        }
+
+        window_def
+            .expected_args
+            .iter()
+            .map(|spec| {
+                let value = match self.args.get(&VarName(spec.name.0.clone())) {
+                    Some(value) => value.clone(),
+                    None if spec.optional => DynVal::from_string(String::new()),
+                    None => bail!("Missing required argument `{}` for window `{}`", spec.name, self.window_name),
+                };
+                Ok((spec.name.clone(), SimplExpr::Literal(value)))
+            })
+            .collect()
+    }
+}
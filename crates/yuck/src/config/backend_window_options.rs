@@ -10,7 +10,11 @@ use crate::{
 };
 use eww_shared_util::Span;
 
-use super::{attributes::Attributes, window_definition::EnumParseError};
+use super::{
+    attributes::Attributes,
+    window_definition::EnumParseError,
+    window_geometry::{AnchorAlignment, WindowGeometry},
+};
 
 use crate::error::{DiagError, DiagResultExt};
 
@@ -111,6 +115,30 @@ pub struct X11StrutDefinition {
     pub dist: NumWithUnit,
 }
 
+impl X11StrutDefinition {
+    /// Amount of pixels to reserve on the configured side of a monitor of the given size.
+    ///
+    /// If the window is anchored to that side, the space between the side and the window is reserved as well,
+    /// such that other windows neither overlap the window nor leave a gap next to it.
+    pub fn reserved_distance(&self, geometry: Option<&WindowGeometry>, monitor_width: i32, monitor_height: i32) -> u32 {
+        let margin = geometry.map_or(0, |geometry| {
+            let (offset_x, offset_y) = geometry.offset.relative_to(monitor_width, monitor_height);
+            match (self.side, geometry.anchor_point.x, geometry.anchor_point.y) {
+                (Side::Left, AnchorAlignment::START, _) => offset_x,
+                (Side::Right, AnchorAlignment::END, _) => -offset_x,
+                (Side::Top, _, AnchorAlignment::START) => offset_y,
+                (Side::Bottom, _, AnchorAlignment::END) => -offset_y,
+                _ => 0,
+            }
+        });
+        let dist = match self.side {
+            Side::Left | Side::Right => self.dist.pixels_relative_to(monitor_width),
+            Side::Top | Side::Bottom => self.dist.pixels_relative_to(monitor_height),
+        };
+        (dist + margin.max(0)).max(0) as u32
+    }
+}
+
 impl FromAstElementContent for X11StrutDefinition {
     const ELEMENT_NAME: &'static str = "struts";
 
@@ -120,3 +148,34 @@ impl FromAstElementContent for X11StrutDefinition {
         Ok(X11StrutDefinition { side: attrs.primitive_required("side")?, dist: attrs.primitive_required("distance")? })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{config::window_geometry::AnchorPoint, value::Coords};
+
+    fn geometry(anchor_point: AnchorPoint, x: i32, y: i32) -> WindowGeometry {
+        WindowGeometry {
+            anchor_point,
+            offset: Coords { x: NumWithUnit::Pixels(x), y: NumWithUnit::Pixels(y) },
+            ..WindowGeometry::default()
+        }
+    }
+
+    #[test]
+    fn test_reserved_distance_includes_margin() {
+        let top = X11StrutDefinition { side: Side::Top, dist: NumWithUnit::Pixels(30) };
+        let top_center = AnchorPoint { x: AnchorAlignment::CENTER, y: AnchorAlignment::START };
+        assert_eq!(top.reserved_distance(None, 1920, 1080), 30);
+        assert_eq!(top.reserved_distance(Some(&geometry(top_center, 0, 10)), 1920, 1080), 40);
+
+        let bottom = X11StrutDefinition { side: Side::Bottom, dist: NumWithUnit::Percent(10.0) };
+        let bottom_left = AnchorPoint { x: AnchorAlignment::START, y: AnchorAlignment::END };
+        assert_eq!(bottom.reserved_distance(Some(&geometry(bottom_left, 20, -8)), 1920, 1000), 108);
+
+        // margins towards other sides, or away from the screen, are not reserved
+        let centered = AnchorPoint { x: AnchorAlignment::CENTER, y: AnchorAlignment::CENTER };
+        assert_eq!(top.reserved_distance(Some(&geometry(centered, 0, 10)), 1920, 1080), 30);
+        assert_eq!(top.reserved_distance(Some(&geometry(top_center, 0, -10)), 1920, 1080), 30);
+    }
+}
@@ -8,6 +8,7 @@ pub mod validate;
 pub mod var_definition;
 pub mod widget_definition;
 pub mod widget_use;
+pub mod window_animation;
 pub mod window_definition;
 pub mod window_geometry;
 
@@ -8,7 +8,7 @@ use itertools::Itertools;
 use super::{
     file_provider::{FilesError, YuckFileProvider},
     script_var_definition::ScriptVarDefinition,
-    var_definition::VarDefinition,
+    var_definition::{DerivedVarDefinition, VarDefinition},
     widget_definition::WidgetDefinition,
     window_definition::WindowDefinition,
 };
@@ -28,6 +28,7 @@ static TOP_LEVEL_DEFINITION_NAMES: &[&str] = &[
     WidgetDefinition::ELEMENT_NAME,
     WindowDefinition::ELEMENT_NAME,
     VarDefinition::ELEMENT_NAME,
+    DerivedVarDefinition::ELEMENT_NAME,
     ListenScriptVar::ELEMENT_NAME,
     PollScriptVar::ELEMENT_NAME,
     Include::ELEMENT_NAME,
@@ -52,6 +53,7 @@ impl FromAstElementContent for Include {
 pub enum TopLevel {
     Include(Include),
     VarDefinition(VarDefinition),
+    DerivedVarDefinition(DerivedVarDefinition),
     ScriptVarDefinition(ScriptVarDefinition),
     WidgetDefinition(WidgetDefinition),
     WindowDefinition(WindowDefinition),
@@ -66,6 +68,9 @@ impl FromAst for TopLevel {
             x if x == Include::ELEMENT_NAME => Self::Include(Include::from_tail(span, iter)?),
             x if x == WidgetDefinition::ELEMENT_NAME => Self::WidgetDefinition(WidgetDefinition::from_tail(span, iter)?),
             x if x == VarDefinition::ELEMENT_NAME => Self::VarDefinition(VarDefinition::from_tail(span, iter)?),
+            x if x == DerivedVarDefinition::ELEMENT_NAME => {
+                Self::DerivedVarDefinition(DerivedVarDefinition::from_tail(span, iter)?)
+            }
             x if x == PollScriptVar::ELEMENT_NAME => {
                 Self::ScriptVarDefinition(ScriptVarDefinition::Poll(PollScriptVar::from_tail(span, iter)?))
             }
@@ -89,14 +94,25 @@ pub struct Config {
     pub widget_definitions: HashMap<String, WidgetDefinition>,
     pub window_definitions: HashMap<String, WindowDefinition>,
     pub var_definitions: HashMap<VarName, VarDefinition>,
+    pub derived_vars: HashMap<VarName, DerivedVarDefinition>,
     pub script_vars: HashMap<VarName, ScriptVarDefinition>,
 }
 
 impl Config {
-    fn append_toplevel(&mut self, files: &mut impl YuckFileProvider, toplevel: TopLevel) -> DiagResult<()> {
+    fn is_var_defined(&self, name: &VarName) -> bool {
+        self.var_definitions.contains_key(name) || self.derived_vars.contains_key(name) || self.script_vars.contains_key(name)
+    }
+
+    /// Add a top-level element to the config. Errors in the elements of included files are added to `errors`.
+    fn append_toplevel(
+        &mut self,
+        files: &mut impl YuckFileProvider,
+        toplevel: TopLevel,
+        errors: &mut Vec<DiagError>,
+    ) -> DiagResult<()> {
         match toplevel {
             TopLevel::VarDefinition(x) => {
-                if self.var_definitions.contains_key(&x.name) || self.script_vars.contains_key(&x.name) {
+                if self.is_var_defined(&x.name) {
                     return Err(DiagError(gen_diagnostic! {
                         msg = format!("Variable {} defined twice", x.name),
                         label = x.span => "defined again here",
@@ -105,8 +121,18 @@ impl Config {
                     self.var_definitions.insert(x.name.clone(), x);
                 }
             }
+            TopLevel::DerivedVarDefinition(x) => {
+                if self.is_var_defined(&x.name) {
+                    return Err(DiagError(gen_diagnostic! {
+                        msg = format!("Variable {} defined twice", x.name),
+                        label = x.span => "defined again here",
+                    }));
+                } else {
+                    self.derived_vars.insert(x.name.clone(), x);
+                }
+            }
             TopLevel::ScriptVarDefinition(x) => {
-                if self.var_definitions.contains_key(x.name()) || self.script_vars.contains_key(x.name()) {
+                if self.is_var_defined(x.name()) {
                     return Err(DiagError(gen_diagnostic! {
                         msg = format!("Variable {} defined twice", x.name()),
                         label = x.name_span() => "defined again here",
@@ -129,32 +155,65 @@ impl Config {
                     }),
                     FilesError::DiagError(x) => x,
                 })?;
-                for element in toplevels {
-                    self.append_toplevel(files, TopLevel::from_ast(element)?)?;
-                }
+                self.append_elements(files, toplevels, errors);
             }
         }
         Ok(())
     }
 
+    /// Add all given top-level elements to the config, skipping the invalid ones and adding their errors to `errors`.
+    fn append_elements(&mut self, files: &mut impl YuckFileProvider, elements: Vec<Ast>, errors: &mut Vec<DiagError>) {
+        for element in elements {
+            let result = TopLevel::from_ast(element).and_then(|toplevel| self.append_toplevel(files, toplevel, errors));
+            if let Err(err) = result {
+                errors.push(err);
+            }
+        }
+    }
+
     pub fn generate(files: &mut impl YuckFileProvider, elements: Vec<Ast>) -> DiagResult<Self> {
+        let (config, mut errors) = Self::generate_collecting_errors(files, elements);
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors.remove(0))
+        }
+    }
+
+    /// Generate the config like [`Self::generate`], but don't stop at the first invalid top-level element.
+    /// Returns the config made of all valid elements, together with the errors of all invalid ones.
+    pub fn generate_collecting_errors(files: &mut impl YuckFileProvider, elements: Vec<Ast>) -> (Self, Vec<DiagError>) {
         let mut config = Self {
             widget_definitions: HashMap::new(),
             window_definitions: HashMap::new(),
             var_definitions: HashMap::new(),
+            derived_vars: HashMap::new(),
             script_vars: HashMap::new(),
         };
-        for element in elements {
-            config.append_toplevel(files, TopLevel::from_ast(element)?)?;
-        }
-        Ok(config)
+        let mut errors = Vec::new();
+        config.append_elements(files, elements, &mut errors);
+        (config, errors)
     }
 
     pub fn generate_from_main_file(files: &mut impl YuckFileProvider, path: impl AsRef<Path>) -> DiagResult<Self> {
+        let (config, mut errors) = Self::generate_from_main_file_collecting_errors(files, path)?;
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors.remove(0))
+        }
+    }
+
+    /// Like [`Self::generate_collecting_errors`], reading the elements from the given file.
+    /// Fails if the file itself can't be read or parsed.
+    pub fn generate_from_main_file_collecting_errors(
+        files: &mut impl YuckFileProvider,
+        path: impl AsRef<Path>,
+    ) -> DiagResult<(Self, Vec<DiagError>)> {
         let (_span, top_levels) = files.load_yuck_file(path.as_ref().to_path_buf()).map_err(|err| match err {
             FilesError::IoError(err) => DiagError(gen_diagnostic!(err)),
             FilesError::DiagError(x) => x,
         })?;
-        Self::generate(files, top_levels)
+        Ok(Self::generate_collecting_errors(files, top_levels))
     }
 }
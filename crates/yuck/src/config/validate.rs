@@ -1,8 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
+use itertools::Itertools;
 use simplexpr::SimplExpr;
 
-use super::{widget_definition::WidgetDefinition, widget_use::WidgetUse, Config};
+use super::{
+    var_definition::DerivedVarDefinition, widget_definition::WidgetDefinition, widget_use::WidgetUse,
+    window_definition::WindowDefinition, Config,
+};
 use eww_shared_util::{AttrName, Span, Spanned, VarName};
 
 #[derive(Debug, thiserror::Error)]
@@ -20,6 +24,14 @@ pub enum ValidationError {
         /// True if the error occurred inside a widget definition, false if it occurred in a window definition
         in_definition: bool,
     },
+
+    #[error("Derived variable `{name}` depends on itself")]
+    DerivedVarCycle {
+        span: Span,
+        name: VarName,
+        /// The variables forming the cycle, starting and ending with the same variable
+        cycle: Vec<VarName>,
+    },
 }
 
 impl Spanned for ValidationError {
@@ -27,26 +39,94 @@ impl Spanned for ValidationError {
         match self {
             ValidationError::MissingAttr { use_span, .. } => *use_span,
             ValidationError::UnknownVariable { span, .. } => *span,
+            ValidationError::DerivedVarCycle { span, .. } => *span,
             ValidationError::AccidentalBuiltinOverride(span, ..) => *span,
         }
     }
 }
 
 pub fn validate(config: &Config, additional_globals: Vec<VarName>) -> Result<(), ValidationError> {
-    let var_names = std::iter::empty()
+    match validate_all(config, additional_globals).into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Validate the config like [`validate`], but report the first error of every definition instead of only the first error overall.
+pub fn validate_all(config: &Config, additional_globals: Vec<VarName>) -> Vec<ValidationError> {
+    let var_names: HashSet<VarName> = std::iter::empty()
         .chain(additional_globals.iter().cloned())
         .chain(config.script_vars.keys().cloned())
         .chain(config.var_definitions.keys().cloned())
+        .chain(config.derived_vars.keys().cloned())
         .collect();
+    let mut errors = Vec::new();
+    for derived_var in config.derived_vars.values() {
+        let unknown_var = derived_var.expr.var_refs_with_span().into_iter().find(|(_, var_ref)| !var_names.contains(*var_ref));
+        if let Some((span, var)) = unknown_var {
+            errors.push(ValidationError::UnknownVariable { span, name: var.clone(), in_definition: false });
+        }
+    }
+    if let Some(cycle) = find_derived_var_cycle(&config.derived_vars) {
+        let name = cycle[0].clone();
+        errors.push(ValidationError::DerivedVarCycle { span: config.derived_vars[&name].span, name, cycle });
+    }
     for window in config.window_definitions.values() {
-        validate_variables_in_widget_use(&config.widget_definitions, &var_names, &window.widget, false)?;
+        errors.extend(validate_window_definition(&config.widget_definitions, &var_names, window).err());
     }
     for def in config.widget_definitions.values() {
-        validate_widget_definition(&config.widget_definitions, &var_names, def)?;
+        errors.extend(validate_widget_definition(&config.widget_definitions, &var_names, def).err());
+    }
+    errors
+}
+
+pub fn validate_window_definition(
+    widget_defs: &HashMap<String, WidgetDefinition>,
+    globals: &HashSet<VarName>,
+    window: &WindowDefinition,
+) -> Result<(), ValidationError> {
+    let mut variables_in_scope = globals.clone();
+    for arg in window.expected_args.iter() {
+        variables_in_scope.insert(VarName(arg.name.to_string()));
+    }
+    validate_variables_in_widget_use(widget_defs, &variables_in_scope, &window.widget, false)?;
+    if let Some(close_when) = &window.close_when {
+        let unknown_var = close_when.var_refs_with_span().into_iter().find(|(_, var_ref)| !variables_in_scope.contains(*var_ref));
+        if let Some((span, var)) = unknown_var {
+            return Err(ValidationError::UnknownVariable { span, name: var.clone(), in_definition: false });
+        }
     }
     Ok(())
 }
 
+/// Find derived variables that depend on themselves, either directly or through other derived variables.
+/// Returns the variables forming the first cycle found.
+pub fn find_derived_var_cycle(derived_vars: &HashMap<VarName, DerivedVarDefinition>) -> Option<Vec<VarName>> {
+    fn visit<'a>(
+        derived_vars: &'a HashMap<VarName, DerivedVarDefinition>,
+        name: &'a VarName,
+        path: &mut Vec<&'a VarName>,
+        done: &mut HashSet<&'a VarName>,
+    ) -> Option<Vec<VarName>> {
+        if let Some(index) = path.iter().position(|var| *var == name) {
+            return Some(path[index..].iter().map(|var| (*var).clone()).chain(std::iter::once(name.clone())).collect());
+        }
+        let definition = derived_vars.get(name).filter(|_| !done.contains(name))?;
+        path.push(name);
+        for (_, var_ref) in definition.expr.var_refs_with_span() {
+            if let Some(cycle) = visit(derived_vars, var_ref, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(name);
+        None
+    }
+
+    let mut done = HashSet::new();
+    derived_vars.keys().sorted_by(|a, b| a.0.cmp(&b.0)).find_map(|name| visit(derived_vars, name, &mut Vec::new(), &mut done))
+}
+
 pub fn validate_widget_definition(
     other_defs: &HashMap<String, WidgetDefinition>,
     globals: &HashSet<VarName>,
@@ -115,3 +195,50 @@ pub fn validate_variables_in_widget_use(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn derived_var(name: &str, expr: &str) -> (VarName, DerivedVarDefinition) {
+        let expr = simplexpr::parse_string(0, 0, expr).unwrap();
+        (VarName::from(name), DerivedVarDefinition { name: VarName::from(name), expr, span: Span::DUMMY })
+    }
+
+    #[test]
+    fn test_find_derived_var_cycle() {
+        let acyclic = HashMap::from([derived_var("a", "b + c"), derived_var("b", "c * 2")]);
+        assert_eq!(find_derived_var_cycle(&acyclic), None);
+
+        let cyclic = HashMap::from([derived_var("a", "b + 1"), derived_var("b", "c"), derived_var("c", "a")]);
+        let cycle = ["a", "b", "c", "a"].map(VarName::from).to_vec();
+        assert_eq!(find_derived_var_cycle(&cyclic), Some(cycle));
+
+        let self_referencing = HashMap::from([derived_var("a", "a + 1")]);
+        assert_eq!(find_derived_var_cycle(&self_referencing), Some(vec![VarName::from("a"), VarName::from("a")]));
+    }
+
+    #[test]
+    fn test_validate_all_reports_every_definition() {
+        let config = Config {
+            widget_definitions: HashMap::new(),
+            window_definitions: HashMap::new(),
+            var_definitions: HashMap::new(),
+            derived_vars: HashMap::from([
+                derived_var("a", "unknown_one"),
+                derived_var("b", "unknown_two"),
+                derived_var("c", "a"),
+            ]),
+            script_vars: HashMap::new(),
+        };
+        let unknown_names = validate_all(&config, Vec::new())
+            .into_iter()
+            .map(|err| match err {
+                ValidationError::UnknownVariable { name, .. } => name,
+                err => panic!("unexpected error: {}", err),
+            })
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+            .collect::<Vec<_>>();
+        assert_eq!(unknown_names, vec![VarName::from("unknown_one"), VarName::from("unknown_two")]);
+    }
+}
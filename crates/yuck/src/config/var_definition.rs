@@ -1,4 +1,4 @@
-use simplexpr::dynval::DynVal;
+use simplexpr::{dynval::DynVal, SimplExpr};
 
 use crate::{
     error::{DiagResult, DiagResultExt},
@@ -26,3 +26,26 @@ impl FromAstElementContent for VarDefinition {
         result.note(r#"Expected format: `(defvar name "initial-value")`"#)
     }
 }
+
+/// A variable whose value is computed from an expression over other variables,
+/// and recomputed whenever any of them changes.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
+pub struct DerivedVarDefinition {
+    pub name: VarName,
+    pub expr: SimplExpr,
+    pub span: Span,
+}
+
+impl FromAstElementContent for DerivedVarDefinition {
+    const ELEMENT_NAME: &'static str = "defexpr";
+
+    fn from_tail<I: Iterator<Item = Ast>>(span: Span, mut iter: AstIterator<I>) -> DiagResult<Self> {
+        let result: DiagResult<_> = try {
+            let (_, name) = iter.expect_symbol()?;
+            let (_, expr) = iter.expect_simplexpr()?;
+            iter.expect_done()?;
+            Self { name: VarName(name), expr, span }
+        };
+        result.note(r#"Expected format: `(defexpr name {some-expression})`"#)
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use simplexpr::dynval::DynVal;
+
+use crate::{
+    error::{DiagError, DiagResult, DiagResultExt},
+    format_diagnostic::ToDiagnostic,
+    parser::{ast::Ast, ast_iterator::AstIterator, from_ast::FromAstElementContent},
+};
+use eww_shared_util::Span;
+
+use super::backend_window_options::Side;
+
+/// Animation of a window sliding in from outside of an edge of its monitor when it is opened,
+/// and sliding back out when it is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct SlideAnimation {
+    /// The edge of the monitor the window slides in from
+    pub from: Side,
+    pub duration: Duration,
+}
+
+impl FromAstElementContent for SlideAnimation {
+    const ELEMENT_NAME: &'static str = "slide";
+
+    fn from_tail<I: Iterator<Item = Ast>>(_span: Span, mut iter: AstIterator<I>) -> DiagResult<Self> {
+        let mut attrs = iter.expect_key_values()?;
+        iter.expect_done().map_err(DiagError::from).note("Check if you are missing a colon in front of a key")?;
+        let duration = match attrs.primitive_optional::<DynVal, _>("duration")? {
+            Some(duration) => duration.as_duration().map_err(|e| DiagError(e.to_diagnostic()))?,
+            None => Duration::from_millis(200),
+        };
+        Ok(SlideAnimation { from: attrs.primitive_required("from")?, duration })
+    }
+}
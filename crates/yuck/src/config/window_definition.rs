@@ -3,25 +3,46 @@ use std::fmt::Display;
 use crate::{
     config::monitor::MonitorIdentifier,
     error::{DiagError, DiagResult},
+    gen_diagnostic,
     parser::{
         ast::Ast,
         ast_iterator::AstIterator,
         from_ast::{FromAst, FromAstElementContent},
     },
 };
-use eww_shared_util::Span;
+use eww_shared_util::{AttrName, Span, Spanned};
+use simplexpr::SimplExpr;
 
-use super::{backend_window_options::BackendWindowOptions, widget_use::WidgetUse, window_geometry::WindowGeometry};
+use super::{
+    backend_window_options::BackendWindowOptions, widget_definition::AttrSpec, widget_use::WidgetUse,
+    window_animation::SlideAnimation, window_geometry::WindowGeometry,
+};
 
 #[derive(Debug, Clone, serde::Serialize, PartialEq)]
 pub struct WindowDefinition {
     pub name: String,
+    /// Arguments that need to be provided when opening the window. These are available as variables within the window.
+    pub expected_args: Vec<AttrSpec>,
     pub geometry: Option<WindowGeometry>,
     pub stacking: WindowStacking,
     pub monitor: Option<MonitorIdentifier>,
     pub widget: WidgetUse,
     pub resizable: bool,
     pub backend_options: BackendWindowOptions,
+    /// Condition that causes the window to be closed once it becomes true
+    pub close_when: Option<SimplExpr>,
+    /// Opacity of the whole window, between 0 and 1
+    pub opacity: Option<f64>,
+    /// Whether the window is opened when the daemon starts
+    pub open_on_startup: bool,
+    /// Whether the window may be placed entirely outside of its monitor, instead of being moved back onto it
+    pub allow_offscreen: bool,
+    /// Animation used when opening and closing the window
+    pub animation: Option<SlideAnimation>,
+    /// Name of the cursor shown while hovering the window, such as `crosshair`
+    pub cursor: Option<String>,
+    /// Whether pointer input passes through the window to the windows below it
+    pub pass_through: bool,
 }
 
 impl FromAstElementContent for WindowDefinition {
@@ -29,15 +50,52 @@ impl FromAstElementContent for WindowDefinition {
 
     fn from_tail<I: Iterator<Item = Ast>>(_span: Span, mut iter: AstIterator<I>) -> DiagResult<Self> {
         let (_, name) = iter.expect_symbol()?;
+        // The argument list is optional, as most windows don't take any arguments
+        let expected_args = match iter.expect_array() {
+            Ok((_, args)) => args.into_iter().map(AttrSpec::from_ast).collect::<DiagResult<_>>()?,
+            Err(_) => Vec::new(),
+        };
         let mut attrs = iter.expect_key_values()?;
         let monitor = attrs.primitive_optional("monitor")?;
         let resizable = attrs.primitive_optional("resizable")?.unwrap_or(true);
+        let open_on_startup = attrs.primitive_optional("open-on-startup")?.unwrap_or(false);
+        let allow_offscreen = attrs.primitive_optional("allow-offscreen")?.unwrap_or(false);
         let stacking = attrs.primitive_optional("stacking")?.unwrap_or(WindowStacking::Foreground);
         let geometry = attrs.ast_optional("geometry")?;
+        let animation = attrs.ast_optional("animation")?;
+        let cursor = attrs.primitive_optional("cursor")?;
+        let pass_through = attrs.primitive_optional("pass-through")?.unwrap_or(false);
+        let close_when = attrs.ast_optional::<SimplExpr>("close-when")?;
+        let opacity_span = attrs.attrs.get(&AttrName::from("opacity")).map(|entry| entry.value.span());
+        let opacity: Option<f64> = attrs.primitive_optional("opacity")?;
+        if let (Some(opacity), Some(opacity_span)) = (opacity, opacity_span) {
+            if !(0.0..=1.0).contains(&opacity) {
+                return Err(DiagError(gen_diagnostic! {
+                    msg = format!("The opacity of a window must be between 0 and 1, but got {}", opacity),
+                    label = opacity_span
+                }));
+            }
+        }
         let backend_options = BackendWindowOptions::from_attrs(&mut attrs)?;
         let widget = iter.expect_any().map_err(DiagError::from).and_then(WidgetUse::from_ast)?;
         iter.expect_done()?;
-        Ok(Self { name, monitor, resizable, widget, stacking, geometry, backend_options })
+        Ok(Self {
+            name,
+            expected_args,
+            monitor,
+            resizable,
+            widget,
+            stacking,
+            geometry,
+            backend_options,
+            close_when,
+            opacity,
+            open_on_startup,
+            allow_offscreen,
+            animation,
+            cursor,
+            pass_through,
+        })
     }
 }
 
@@ -84,6 +142,18 @@ pub enum WindowStacking {
     Overlay,
 }
 
+impl WindowStacking {
+    /// Whether the window should be kept above other windows, on backends that only distinguish between above and below.
+    pub fn is_above(&self) -> bool {
+        matches!(self, WindowStacking::Foreground | WindowStacking::Overlay)
+    }
+
+    /// Whether the window should be kept below other windows, on backends that only distinguish between above and below.
+    pub fn is_below(&self) -> bool {
+        matches!(self, WindowStacking::Background | WindowStacking::Bottom)
+    }
+}
+
 impl std::str::FromStr for WindowStacking {
     type Err = EnumParseError;
 
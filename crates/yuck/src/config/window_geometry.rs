@@ -3,7 +3,7 @@ use crate::{
     error::DiagResult,
     format_diagnostic::ToDiagnostic,
     parser::{ast::Ast, ast_iterator::AstIterator, from_ast::FromAstElementContent},
-    value::Coords,
+    value::{Coords, NumWithUnit},
 };
 
 use super::window_definition::EnumParseError;
@@ -107,6 +107,10 @@ pub struct WindowGeometry {
     pub anchor_point: AnchorPoint,
     pub offset: Coords,
     pub size: Coords,
+    /// Upper limit for the width of the window, also when it is sized to fit its content
+    pub max_width: Option<NumWithUnit>,
+    /// Upper limit for the height of the window, also when it is sized to fit its content
+    pub max_height: Option<NumWithUnit>,
 }
 
 impl FromAstElementContent for WindowGeometry {
@@ -126,6 +130,8 @@ impl FromAstElementContent for WindowGeometry {
                 x: attrs.primitive_optional("x")?.unwrap_or_default(),
                 y: attrs.primitive_optional("y")?.unwrap_or_default(),
             },
+            max_width: attrs.primitive_optional("max-width")?,
+            max_height: attrs.primitive_optional("max-height")?,
         })
     }
 }
@@ -136,6 +142,7 @@ impl WindowGeometry {
             anchor_point: anchor_point.unwrap_or(self.anchor_point),
             offset: offset.unwrap_or(self.offset),
             size: size.unwrap_or(self.size),
+            ..*self
         }
     }
 }
@@ -145,3 +152,33 @@ impl std::fmt::Display for WindowGeometry {
         write!(f, "{}-{} ({})", self.offset, self.size, self.anchor_point)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use AnchorAlignment::*;
+
+    #[test]
+    fn test_parse_anchor_point() {
+        let cases = [
+            ("top left", START, START),
+            ("top center", CENTER, START),
+            ("top right", END, START),
+            ("center left", START, CENTER),
+            ("center", CENTER, CENTER),
+            ("center right", END, CENTER),
+            ("bottom left", START, END),
+            ("bottom center", CENTER, END),
+            ("bottom right", END, END),
+        ];
+        for (input, x, y) in cases {
+            let anchor_point: AnchorPoint = input.parse().unwrap();
+            assert_eq!(anchor_point, AnchorPoint { x, y }, "parsing `{}`", input);
+            assert_eq!(anchor_point.to_string().parse::<AnchorPoint>().unwrap(), anchor_point);
+        }
+        assert_eq!("left top".parse::<AnchorPoint>().unwrap(), AnchorPoint { x: START, y: START });
+        assert_eq!("c b".parse::<AnchorPoint>().unwrap(), AnchorPoint { x: CENTER, y: END });
+        assert!("top bottom".parse::<AnchorPoint>().is_err());
+        assert!("topleft".parse::<AnchorPoint>().is_err());
+    }
+}
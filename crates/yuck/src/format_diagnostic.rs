@@ -142,6 +142,11 @@ impl ToDiagnostic for ValidationError {
 
                 diag.with_notes(extra_notes)
             }
+            ValidationError::DerivedVarCycle { span, cycle, .. } => gen_diagnostic! {
+                msg = self,
+                label = span => "Defined here",
+                note = format!("Dependency cycle: {}", cycle.iter().map(|name| format!("`{}`", name)).join(" -> "))
+            },
             ValidationError::AccidentalBuiltinOverride(span, _widget_name) => gen_diagnostic! {
                 msg = self,
                 label = span => "Defined here",
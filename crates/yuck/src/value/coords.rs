@@ -108,6 +108,7 @@ mod test {
         assert_eq!(NumWithUnit::Pixels(55), NumWithUnit::from_str("55px").unwrap());
         assert_eq!(NumWithUnit::Percent(55.0), NumWithUnit::from_str("55%").unwrap());
         assert_eq!(NumWithUnit::Percent(55.5), NumWithUnit::from_str("55.5%").unwrap());
+        assert_eq!(NumWithUnit::Pixels(-20), NumWithUnit::from_str("-20").unwrap());
         assert!(NumWithUnit::from_str("55pp").is_err());
     }
 